@@ -0,0 +1,30 @@
+//! Vector generators for the range proof
+extern crate alloc;
+
+use alloc::vec::Vec;
+use jubjub::extend::Extended;
+use jubjub::pedersen::nums_generator;
+
+/// generators `G_i` and `H_i` for up to `capacity` bits
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct BulletproofGens {
+    pub g: Vec<Extended>,
+    pub h: Vec<Extended>,
+}
+
+impl BulletproofGens {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            g: (0..capacity as u32)
+                .map(|i| nums_generator(b"Bulletproofs/G", i))
+                .collect(),
+            h: (0..capacity as u32)
+                .map(|i| nums_generator(b"Bulletproofs/H", i))
+                .collect(),
+        }
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.g.len()
+    }
+}
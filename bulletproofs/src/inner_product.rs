@@ -0,0 +1,230 @@
+//! Inner product argument
+//!
+//! Proves knowledge of `a, b` with `P = <a, G> + <b, H> + <a, b> * Q` by
+//! halving the vectors `log2(n)` times, each round sends `L` and `R`.
+extern crate alloc;
+
+use crate::util::{inner_product, to_point};
+
+use alloc::vec::Vec;
+use jubjub::affine::Affine;
+use jubjub::extend::{multiscalar_mul, Extended};
+use jubjub::scalar::Scalar;
+use schnorr::transcript::Transcript;
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct InnerProductProof {
+    pub(crate) l_vec: Vec<Affine>,
+    pub(crate) r_vec: Vec<Affine>,
+    pub(crate) a: Scalar,
+    pub(crate) b: Scalar,
+}
+
+impl InnerProductProof {
+    /// panics unless all vectors share a power of two length
+    pub fn create(
+        transcript: &mut Transcript,
+        q: Extended,
+        mut g: Vec<Extended>,
+        mut h: Vec<Extended>,
+        mut a: Vec<Scalar>,
+        mut b: Vec<Scalar>,
+    ) -> Self {
+        let mut n = g.len();
+        assert!(n.is_power_of_two());
+        assert!(h.len() == n && a.len() == n && b.len() == n);
+        transcript.append_u64(b"ipp n", n as u64);
+
+        let mut l_vec = Vec::with_capacity(n.trailing_zeros() as usize);
+        let mut r_vec = Vec::with_capacity(n.trailing_zeros() as usize);
+        while n != 1 {
+            n /= 2;
+            let (a_l, a_r) = a.split_at(n);
+            let (b_l, b_r) = b.split_at(n);
+            let (g_l, g_r) = g.split_at(n);
+            let (h_l, h_r) = h.split_at(n);
+
+            // L = <a_L, G_R> + <b_R, H_L> + <a_L, b_R> * Q
+            let l = multiscalar_mul(
+                &[a_l, b_r, &[inner_product(a_l, b_r)]].concat(),
+                &[g_r, h_l, &[q]].concat(),
+            )
+            .to_affine();
+            // R = <a_R, G_L> + <b_L, H_R> + <a_R, b_L> * Q
+            let r = multiscalar_mul(
+                &[a_r, b_l, &[inner_product(a_r, b_l)]].concat(),
+                &[g_l, h_r, &[q]].concat(),
+            )
+            .to_affine();
+            transcript.append_point(b"L", &l);
+            transcript.append_point(b"R", &r);
+            l_vec.push(l);
+            r_vec.push(r);
+
+            let u = transcript.challenge_scalar(b"u");
+            let u_inv = u.invert().unwrap();
+            a = (0..n).map(|i| a_l[i] * u + a_r[i] * u_inv).collect();
+            b = (0..n).map(|i| b_l[i] * u_inv + b_r[i] * u).collect();
+            g = (0..n).map(|i| g_l[i] * u_inv + g_r[i] * u).collect();
+            h = (0..n).map(|i| h_l[i] * u + h_r[i] * u_inv).collect();
+        }
+
+        Self {
+            l_vec,
+            r_vec,
+            a: a[0],
+            b: b[0],
+        }
+    }
+
+    /// `(u_j^2, u_j^-2, s)` where the folded generators are `<s, G>` and `<s^-1, H>`
+    pub(crate) fn verification_scalars(
+        &self,
+        n: usize,
+        transcript: &mut Transcript,
+    ) -> Option<(Vec<Scalar>, Vec<Scalar>, Vec<Scalar>)> {
+        let lg_n = self.l_vec.len();
+        if lg_n >= 32 || n != 1 << lg_n || self.r_vec.len() != lg_n {
+            return None;
+        }
+        transcript.append_u64(b"ipp n", n as u64);
+
+        let mut challenges = Vec::with_capacity(lg_n);
+        for (l, r) in self.l_vec.iter().zip(self.r_vec.iter()) {
+            transcript.append_point(b"L", l);
+            transcript.append_point(b"R", r);
+            challenges.push(transcript.challenge_scalar(b"u"));
+        }
+        let inverses = challenges
+            .iter()
+            .map(|u| u.invert())
+            .collect::<Option<Vec<Scalar>>>()?;
+
+        let u_sq: Vec<Scalar> = challenges.iter().map(|u| u.square()).collect();
+        let u_inv_sq: Vec<Scalar> = inverses.iter().map(|u| u.square()).collect();
+
+        // s_i = prod u_j^(+1 if bit j of i is set else -1), bits from the first round
+        let mut s = Vec::with_capacity(n);
+        s.push(inverses.iter().fold(Scalar::one(), |acc, u| acc * *u));
+        for i in 1..n {
+            let lg_i = (usize::BITS - 1 - i.leading_zeros()) as usize;
+            let k = 1 << lg_i;
+            s.push(s[i - k] * u_sq[lg_n - 1 - lg_i]);
+        }
+
+        Some((u_sq, u_inv_sq, s))
+    }
+
+    /// `P == <a * s, G> + <b * s^-1, H> + a * b * Q - sum(u_j^2 * L_j + u_j^-2 * R_j)`
+    pub fn verify(
+        &self,
+        transcript: &mut Transcript,
+        q: Extended,
+        p: Extended,
+        g: &[Extended],
+        h: &[Extended],
+    ) -> bool {
+        let n = g.len();
+        if h.len() != n {
+            return false;
+        }
+        let (u_sq, u_inv_sq, s) = match self.verification_scalars(n, transcript) {
+            Some(scalars) => scalars,
+            None => return false,
+        };
+
+        let scalars: Vec<Scalar> = s
+            .iter()
+            .map(|s_i| self.a * *s_i)
+            .chain(s.iter().rev().map(|s_i| self.b * *s_i))
+            .chain([self.a * self.b])
+            .chain(u_sq.iter().map(|u| -*u))
+            .chain(u_inv_sq.iter().map(|u| -*u))
+            .collect();
+        let points: Vec<Extended> = g
+            .iter()
+            .chain(h)
+            .copied()
+            .chain([q])
+            .chain(self.l_vec.iter().map(|l| l.extend()))
+            .chain(self.r_vec.iter().map(|r| r.extend()))
+            .collect();
+
+        multiscalar_mul(&scalars, &points) == p
+    }
+
+    /// `L_0 || R_0 || ... || a || b`
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(64 * self.l_vec.len() + 64);
+        for (l, r) in self.l_vec.iter().zip(self.r_vec.iter()) {
+            bytes.extend_from_slice(&l.to_bytes());
+            bytes.extend_from_slice(&r.to_bytes());
+        }
+        bytes.extend_from_slice(&self.a.to_bytes());
+        bytes.extend_from_slice(&self.b.to_bytes());
+        bytes
+    }
+
+    /// `None` for non-canonical scalars or points outside the prime order subgroup
+    pub fn from_bytes(bytes: &[u8]) -> Option<Self> {
+        if bytes.len() < 64 || !bytes.len().is_multiple_of(64) {
+            return None;
+        }
+        let (rounds, scalars) = bytes.split_at(bytes.len() - 64);
+
+        let mut l_vec = Vec::with_capacity(rounds.len() / 64);
+        let mut r_vec = Vec::with_capacity(rounds.len() / 64);
+        for chunk in rounds.chunks(64) {
+            l_vec.push(to_point(chunk[..32].try_into().unwrap())?);
+            r_vec.push(to_point(chunk[32..].try_into().unwrap())?);
+        }
+
+        Some(Self {
+            l_vec,
+            r_vec,
+            a: Scalar::from_bytes(scalars[..32].try_into().unwrap())?,
+            b: Scalar::from_bytes(scalars[32..].try_into().unwrap())?,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::generators::BulletproofGens;
+    use jubjub::pedersen::PedersenGens;
+    use proptest::{collection::vec, prelude::*};
+
+    prop_compose! {
+        fn arb_field()(
+            bytes in vec(any::<u8>(), 64)
+        ) -> Scalar {
+            Scalar::from_bytes_wide(&<[u8; 64]>::try_from(bytes).unwrap())
+        }
+    }
+
+    proptest! {
+        #![proptest_config(ProptestConfig::with_cases(5))]
+        #[test]
+        fn test_inner_product(a in vec(arb_field(), 8), b in vec(arb_field(), 8)) {
+            let gens = BulletproofGens::new(8);
+            let q = PedersenGens::default().value;
+            let p = multiscalar_mul(
+                &[&a[..], &b[..], &[inner_product(&a, &b)]].concat(),
+                &[&gens.g[..], &gens.h[..], &[q]].concat(),
+            );
+
+            let proof = InnerProductProof::create(
+                &mut Transcript::new(b"ipp"),
+                q,
+                gens.g.clone(),
+                gens.h.clone(),
+                a,
+                b,
+            );
+            assert!(proof.verify(&mut Transcript::new(b"ipp"), q, p, &gens.g, &gens.h));
+            assert!(!proof.verify(&mut Transcript::new(b"ipp"), q, p + q, &gens.g, &gens.h));
+            assert_eq!(InnerProductProof::from_bytes(&proof.to_bytes()), Some(proof))
+        }
+    }
+}
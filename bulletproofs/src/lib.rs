@@ -0,0 +1,5 @@
+#![no_std]
+pub mod generators;
+pub mod inner_product;
+pub mod range_proof;
+mod util;
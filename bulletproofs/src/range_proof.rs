@@ -0,0 +1,421 @@
+//! Single and aggregated range proofs for values in `[0, 2^n)`
+extern crate alloc;
+
+use crate::generators::BulletproofGens;
+use crate::inner_product::InnerProductProof;
+use crate::util::{inner_product, powers, sum, to_point};
+
+use alloc::vec::Vec;
+use jubjub::affine::Affine;
+use jubjub::extend::{multiscalar_mul, Extended};
+use jubjub::pedersen::{Commitment, PedersenGens};
+use jubjub::scalar::Scalar;
+use rand_core::RngCore;
+use schnorr::transcript::Transcript;
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RangeProof {
+    a: Affine,
+    s: Affine,
+    t1: Affine,
+    t2: Affine,
+    // t(x)
+    t_x: Scalar,
+    // tau_x
+    t_x_blinding: Scalar,
+    // mu
+    e_blinding: Scalar,
+    ipp: InnerProductProof,
+}
+
+impl RangeProof {
+    pub fn prove_single(
+        bp_gens: &BulletproofGens,
+        pc_gens: &PedersenGens,
+        transcript: &mut Transcript,
+        value: u64,
+        blinding: Scalar,
+        n: usize,
+        rand: impl RngCore,
+    ) -> Option<(Self, Commitment)> {
+        Self::prove_multiple(bp_gens, pc_gens, transcript, &[value], &[blinding], n, rand)
+            .map(|(proof, commitments)| (proof, commitments[0]))
+    }
+
+    /// `None` for unsupported `n`, a non power of two number of values,
+    /// too few generators or a value out of range
+    pub fn prove_multiple(
+        bp_gens: &BulletproofGens,
+        pc_gens: &PedersenGens,
+        transcript: &mut Transcript,
+        values: &[u64],
+        blindings: &[Scalar],
+        n: usize,
+        mut rand: impl RngCore,
+    ) -> Option<(Self, Vec<Commitment>)> {
+        let m = values.len();
+        if !is_valid(bp_gens, n, m)
+            || blindings.len() != m
+            || (n < 64 && values.iter().any(|v| v >> n != 0))
+        {
+            return None;
+        }
+        let nm = n * m;
+        let (g, h) = (&bp_gens.g[..nm], &bp_gens.h[..nm]);
+
+        let commitments: Vec<Commitment> = values
+            .iter()
+            .zip(blindings)
+            .map(|(v, gamma)| pc_gens.commit(Scalar::from(*v), *gamma))
+            .collect();
+        append_statement(transcript, &commitments, n);
+
+        // A = alpha * B~ + <a_L, G> + <a_R, H> with a_R = a_L - 1
+        let a_l: Vec<Scalar> = values
+            .iter()
+            .flat_map(|v| (0..n).map(move |i| Scalar::from((v >> i) & 1)))
+            .collect();
+        let a_r: Vec<Scalar> = a_l.iter().map(|bit| *bit - Scalar::one()).collect();
+        let alpha = Scalar::random(&mut rand);
+        let a = multiscalar_mul(
+            &[&[alpha], &a_l[..], &a_r[..]].concat(),
+            &[&[pc_gens.blinding], g, h].concat(),
+        )
+        .to_affine();
+
+        // S = rho * B~ + <s_L, G> + <s_R, H>
+        let s_l: Vec<Scalar> = (0..nm).map(|_| Scalar::random(&mut rand)).collect();
+        let s_r: Vec<Scalar> = (0..nm).map(|_| Scalar::random(&mut rand)).collect();
+        let rho = Scalar::random(&mut rand);
+        let s = multiscalar_mul(
+            &[&[rho], &s_l[..], &s_r[..]].concat(),
+            &[&[pc_gens.blinding], g, h].concat(),
+        )
+        .to_affine();
+
+        transcript.append_point(b"A", &a);
+        transcript.append_point(b"S", &s);
+        let y = transcript.challenge_scalar(b"y");
+        let z = transcript.challenge_scalar(b"z");
+
+        // l(x) = (a_L - z) + s_L * x
+        // r(x) = y^nm o (a_R + z + s_R * x) + z^(2 + j) * 2^n
+        let y_pow = powers(y, nm);
+        let z_and_2 = z_and_2(z, n, m);
+        let l_0: Vec<Scalar> = a_l.iter().map(|a| *a - z).collect();
+        let r_0: Vec<Scalar> = (0..nm)
+            .map(|i| y_pow[i] * (a_r[i] + z) + z_and_2[i])
+            .collect();
+        let r_1: Vec<Scalar> = (0..nm).map(|i| y_pow[i] * s_r[i]).collect();
+
+        // t(x) = t_0 + t_1 * x + t_2 * x^2
+        let t_1 = inner_product(&l_0, &r_1) + inner_product(&s_l, &r_0);
+        let t_2 = inner_product(&s_l, &r_1);
+        let tau_1 = Scalar::random(&mut rand);
+        let tau_2 = Scalar::random(&mut rand);
+        let t1 = pc_gens.commit(t_1, tau_1).to_point().to_affine();
+        let t2 = pc_gens.commit(t_2, tau_2).to_point().to_affine();
+
+        transcript.append_point(b"T_1", &t1);
+        transcript.append_point(b"T_2", &t2);
+        let x = transcript.challenge_scalar(b"x");
+
+        let l: Vec<Scalar> = (0..nm).map(|i| l_0[i] + s_l[i] * x).collect();
+        let r: Vec<Scalar> = (0..nm).map(|i| r_0[i] + r_1[i] * x).collect();
+        let t_x = inner_product(&l, &r);
+        let z_pow = powers(z, m + 2);
+        let t_x_blinding = blindings
+            .iter()
+            .zip(&z_pow[2..])
+            .fold(tau_1 * x + tau_2 * x.square(), |acc, (gamma, z_j)| {
+                acc + *gamma * *z_j
+            });
+        let e_blinding = alpha + rho * x;
+
+        transcript.append_scalar(b"t_x", &t_x);
+        transcript.append_scalar(b"t_x_blinding", &t_x_blinding);
+        transcript.append_scalar(b"e_blinding", &e_blinding);
+        let w = transcript.challenge_scalar(b"w");
+
+        // H'_i = y^-i * H_i
+        let y_inv_pow = powers(y.invert()?, nm);
+        let h_prime = h.iter().zip(&y_inv_pow).map(|(h, y)| *h * *y).collect();
+        let ipp =
+            InnerProductProof::create(transcript, pc_gens.value * w, g.to_vec(), h_prime, l, r);
+
+        let proof = Self {
+            a,
+            s,
+            t1,
+            t2,
+            t_x,
+            t_x_blinding,
+            e_blinding,
+            ipp,
+        };
+        Some((proof, commitments))
+    }
+
+    pub fn verify_single(
+        &self,
+        bp_gens: &BulletproofGens,
+        pc_gens: &PedersenGens,
+        transcript: &mut Transcript,
+        commitment: &Commitment,
+        n: usize,
+    ) -> bool {
+        self.verify_multiple(bp_gens, pc_gens, transcript, &[*commitment], n)
+    }
+
+    /// every check folded into one multiscalar multiplication
+    pub fn verify_multiple(
+        &self,
+        bp_gens: &BulletproofGens,
+        pc_gens: &PedersenGens,
+        transcript: &mut Transcript,
+        commitments: &[Commitment],
+        n: usize,
+    ) -> bool {
+        let m = commitments.len();
+        if !is_valid(bp_gens, n, m) {
+            return false;
+        }
+        let nm = n * m;
+        append_statement(transcript, commitments, n);
+
+        transcript.append_point(b"A", &self.a);
+        transcript.append_point(b"S", &self.s);
+        let y = transcript.challenge_scalar(b"y");
+        let z = transcript.challenge_scalar(b"z");
+        transcript.append_point(b"T_1", &self.t1);
+        transcript.append_point(b"T_2", &self.t2);
+        let x = transcript.challenge_scalar(b"x");
+        transcript.append_scalar(b"t_x", &self.t_x);
+        transcript.append_scalar(b"t_x_blinding", &self.t_x_blinding);
+        transcript.append_scalar(b"e_blinding", &self.e_blinding);
+        let w = transcript.challenge_scalar(b"w");
+
+        let (u_sq, u_inv_sq, s) = match self.ipp.verification_scalars(nm, transcript) {
+            Some(scalars) => scalars,
+            None => return false,
+        };
+        // random weight separating the t(x) check from the inner product check
+        let c = transcript.challenge_scalar(b"c");
+        let y_inv = match y.invert() {
+            Some(y_inv) => y_inv,
+            None => return false,
+        };
+
+        let (a, b) = (self.ipp.a, self.ipp.b);
+        let z_sq = z.square();
+        let y_inv_pow = powers(y_inv, nm);
+        let z_and_2 = z_and_2(z, n, m);
+        let z_pow = powers(z, m + 2);
+
+        // delta(y, z) = (z - z^2) * <1, y^nm> - sum(z^(3 + j)) * <1, 2^n>
+        let delta = (z - z_sq) * sum(&powers(y, nm))
+            - sum(&z_pow[2..]) * z * sum(&powers(Scalar::from(2), n));
+
+        let g_scalars = s.iter().map(|s_i| -z - a * *s_i);
+        let h_scalars = (0..nm).map(|i| z + y_inv_pow[i] * (z_and_2[i] - b * s[nm - 1 - i]));
+        let v_scalars = z_pow[2..].iter().map(|z_j| c * *z_j);
+
+        let scalars: Vec<Scalar> = [Scalar::one(), x, c * x, c * x.square()]
+            .into_iter()
+            .chain(u_sq)
+            .chain(u_inv_sq)
+            .chain([
+                -self.e_blinding - c * self.t_x_blinding,
+                w * (self.t_x - a * b) + c * (delta - self.t_x),
+            ])
+            .chain(g_scalars)
+            .chain(h_scalars)
+            .chain(v_scalars)
+            .collect();
+        let points: Vec<Extended> = [self.a, self.s, self.t1, self.t2]
+            .iter()
+            .chain(&self.ipp.l_vec)
+            .chain(&self.ipp.r_vec)
+            .map(|point| point.extend())
+            .chain([pc_gens.blinding, pc_gens.value])
+            .chain(bp_gens.g[..nm].iter().copied())
+            .chain(bp_gens.h[..nm].iter().copied())
+            .chain(commitments.iter().map(|v| v.to_point()))
+            .collect();
+
+        multiscalar_mul(&scalars, &points).is_identity()
+    }
+
+    /// `A || S || T_1 || T_2 || t_x || tau_x || mu || inner product proof`
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        for point in [self.a, self.s, self.t1, self.t2] {
+            bytes.extend_from_slice(&point.to_bytes());
+        }
+        for scalar in [self.t_x, self.t_x_blinding, self.e_blinding] {
+            bytes.extend_from_slice(&scalar.to_bytes());
+        }
+        bytes.extend_from_slice(&self.ipp.to_bytes());
+        bytes
+    }
+
+    /// `None` for non-canonical scalars or points outside the prime order subgroup
+    pub fn from_bytes(bytes: &[u8]) -> Option<Self> {
+        if bytes.len() < 7 * 32 {
+            return None;
+        }
+        let chunk = |i: usize| -> [u8; 32] { bytes[32 * i..32 * (i + 1)].try_into().unwrap() };
+        let point = |i: usize| to_point(chunk(i));
+        let scalar = |i: usize| Scalar::from_bytes(chunk(i));
+
+        Some(Self {
+            a: point(0)?,
+            s: point(1)?,
+            t1: point(2)?,
+            t2: point(3)?,
+            t_x: scalar(4)?,
+            t_x_blinding: scalar(5)?,
+            e_blinding: scalar(6)?,
+            ipp: InnerProductProof::from_bytes(&bytes[7 * 32..])?,
+        })
+    }
+}
+
+fn is_valid(bp_gens: &BulletproofGens, n: usize, m: usize) -> bool {
+    matches!(n, 8 | 16 | 32 | 64) && m.is_power_of_two() && bp_gens.capacity() >= n * m
+}
+
+fn append_statement(transcript: &mut Transcript, commitments: &[Commitment], n: usize) {
+    transcript.append_u64(b"n", n as u64);
+    transcript.append_u64(b"m", commitments.len() as u64);
+    for commitment in commitments {
+        transcript.append_message(b"V", &commitment.to_bytes());
+    }
+}
+
+// z^(2 + j) * 2^i for the i-th bit of the j-th value
+fn z_and_2(z: Scalar, n: usize, m: usize) -> Vec<Scalar> {
+    let two_pow = powers(Scalar::from(2), n);
+    powers(z, m + 2)[2..]
+        .iter()
+        .flat_map(|z_j| two_pow.iter().map(move |two| *z_j * *two))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use proptest::{collection::vec, prelude::*};
+    use rand_core::OsRng;
+
+    prop_compose! {
+        fn arb_field()(
+            bytes in vec(any::<u8>(), 64)
+        ) -> Scalar {
+            Scalar::from_bytes_wide(&<[u8; 64]>::try_from(bytes).unwrap())
+        }
+    }
+
+    proptest! {
+        #![proptest_config(ProptestConfig::with_cases(3))]
+        #[test]
+        fn test_single_range_proof(value in any::<u64>(), blinding in arb_field()) {
+            let bp_gens = BulletproofGens::new(64);
+            let pc_gens = PedersenGens::default();
+
+            let (proof, commitment) = RangeProof::prove_single(&bp_gens, &pc_gens, &mut Transcript::new(b"range"), value, blinding, 64, OsRng).unwrap();
+            let decoded = RangeProof::from_bytes(&proof.to_bytes()).unwrap();
+
+            assert_eq!(decoded, proof);
+            assert!(proof.verify_single(&bp_gens, &pc_gens, &mut Transcript::new(b"range"), &commitment, 64));
+            assert!(!proof.verify_single(&bp_gens, &pc_gens, &mut Transcript::new(b"other"), &commitment, 64));
+            assert!(!proof.verify_single(&bp_gens, &pc_gens, &mut Transcript::new(b"range"), &(commitment + commitment), 64))
+        }
+    }
+
+    proptest! {
+        #![proptest_config(ProptestConfig::with_cases(3))]
+        #[test]
+        fn test_aggregated_range_proof(values in vec(any::<u16>(), 4), blindings in vec(arb_field(), 4)) {
+            let bp_gens = BulletproofGens::new(64);
+            let pc_gens = PedersenGens::default();
+            let values: Vec<u64> = values.into_iter().map(u64::from).collect();
+
+            let (proof, commitments) = RangeProof::prove_multiple(&bp_gens, &pc_gens, &mut Transcript::new(b"range"), &values, &blindings, 16, OsRng).unwrap();
+
+            assert!(proof.verify_multiple(&bp_gens, &pc_gens, &mut Transcript::new(b"range"), &commitments, 16));
+            assert!(!proof.verify_multiple(&bp_gens, &pc_gens, &mut Transcript::new(b"range"), &commitments[..2], 16))
+        }
+    }
+
+    #[test]
+    fn test_torsion_rejected() {
+        let bp_gens = BulletproofGens::new(8);
+        let pc_gens = PedersenGens::default();
+        let (proof, _) = RangeProof::prove_single(
+            &bp_gens,
+            &pc_gens,
+            &mut Transcript::new(b"range"),
+            7,
+            Scalar::random(OsRng),
+            8,
+            OsRng,
+        )
+        .unwrap();
+        // order two point (0, -1)
+        let order_two = Affine::from_bytes((-jubjub::Base::one()).to_bytes()).unwrap();
+        let bytes = proof.to_bytes();
+
+        let mut small = bytes.clone();
+        small[..32].copy_from_slice(&order_two.to_bytes());
+        let mut mixed = bytes.clone();
+        mixed[..32].copy_from_slice(&(proof.a.extend() + order_two).to_bytes());
+        let mut mixed_l = bytes.clone();
+        mixed_l[7 * 32..8 * 32]
+            .copy_from_slice(&(proof.ipp.l_vec[0].extend() + order_two).to_bytes());
+
+        assert!(RangeProof::from_bytes(&small).is_none());
+        assert!(RangeProof::from_bytes(&mixed).is_none());
+        assert!(RangeProof::from_bytes(&mixed_l).is_none());
+        assert!(InnerProductProof::from_bytes(&mixed_l[7 * 32..]).is_none());
+        assert_eq!(RangeProof::from_bytes(&bytes), Some(proof));
+    }
+
+    #[test]
+    fn test_out_of_range() {
+        let bp_gens = BulletproofGens::new(8);
+        let pc_gens = PedersenGens::default();
+        let blinding = Scalar::random(OsRng);
+
+        assert!(RangeProof::prove_single(
+            &bp_gens,
+            &pc_gens,
+            &mut Transcript::new(b"range"),
+            256,
+            blinding,
+            8,
+            OsRng
+        )
+        .is_none());
+        assert!(RangeProof::prove_single(
+            &bp_gens,
+            &pc_gens,
+            &mut Transcript::new(b"range"),
+            1,
+            blinding,
+            16,
+            OsRng
+        )
+        .is_none());
+        assert!(RangeProof::prove_single(
+            &bp_gens,
+            &pc_gens,
+            &mut Transcript::new(b"range"),
+            255,
+            blinding,
+            8,
+            OsRng
+        )
+        .is_some())
+    }
+}
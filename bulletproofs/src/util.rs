@@ -0,0 +1,33 @@
+extern crate alloc;
+
+use alloc::vec::Vec;
+use jubjub::affine::Affine;
+use jubjub::scalar::Scalar;
+
+/// `1, x, x^2, ..., x^(n - 1)`
+pub(crate) fn powers(x: Scalar, n: usize) -> Vec<Scalar> {
+    let mut power = Scalar::one();
+    (0..n)
+        .map(|_| {
+            let current = power;
+            power = power * x;
+            current
+        })
+        .collect()
+}
+
+pub(crate) fn inner_product(a: &[Scalar], b: &[Scalar]) -> Scalar {
+    a.iter()
+        .zip(b)
+        .fold(Scalar::zero(), |acc, (a, b)| acc + *a * *b)
+}
+
+pub(crate) fn sum(a: &[Scalar]) -> Scalar {
+    a.iter().fold(Scalar::zero(), |acc, a| acc + *a)
+}
+
+/// decoded point, `None` unless it lies in the prime order subgroup
+pub(crate) fn to_point(bytes: [u8; 32]) -> Option<Affine> {
+    let point = Affine::from_bytes(bytes)?;
+    point.extend().is_torsion_free().then_some(point)
+}
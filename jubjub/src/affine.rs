@@ -31,6 +31,8 @@ const Y: Base = Base::to_mont([
     0x0000000000000000,
 ]);
 
+// x * y of the generator
+#[allow(dead_code)]
 const T: Base = Base::to_mont([
     0xd3ba1512623479e1,
     0xc6e03c0fcb495697,
@@ -50,14 +52,14 @@ impl Affine {
         Self { x: X, y: Y }
     }
 
-    pub(crate) fn identity() -> Self {
+    pub fn identity() -> Self {
         Self {
             x: Base::zero(),
             y: Base::one(),
         }
     }
 
-    pub(crate) fn extend(self) -> Extended {
+    pub fn extend(self) -> Extended {
         Extended {
             x: self.x,
             y: self.y,
@@ -107,6 +109,13 @@ impl Affine {
     pub fn double(self) -> Extended {
         double_affine_point(self)
     }
+
+    /// -x^2 + y^2 = 1 + dx^2y^2
+    pub fn is_on_curve(self) -> bool {
+        let x2 = self.x.square();
+        let y2 = self.y.square();
+        y2 - x2 == Base::one() + D * x2 * y2
+    }
 }
 
 impl Add for Affine {
@@ -166,6 +175,11 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_generator() {
+        assert_eq!(X * Y, T)
+    }
+
     proptest! {
         #![proptest_config(ProptestConfig::with_cases(100))]
         #[test]
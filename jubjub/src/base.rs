@@ -1,6 +1,6 @@
 //! Jubjub base field
 use core::fmt::{Debug, Formatter, Result};
-use core::ops::{Add, Mul, Neg, Sub};
+use core::ops::{Add, AddAssign, Mul, MulAssign, Neg, Sub};
 
 use crate::limbs::{add, double, from_u512, invert, little_fermat, mont, mul, neg, square, sub};
 use crate::math::sbb;
@@ -49,7 +49,7 @@ const ROOT_OF_UNITY: Base = Base([
 
 // Bls scalar and Jubjub base field
 #[derive(Clone, Copy, PartialEq, Eq)]
-pub(crate) struct Base(pub [u64; 4]);
+pub struct Base(pub(crate) [u64; 4]);
 
 impl Base {
     // map raw limbs to montgomery form
@@ -66,11 +66,11 @@ impl Base {
         )
     }
 
-    pub(crate) fn zero() -> Self {
+    pub fn zero() -> Self {
         Self([0; 4])
     }
 
-    pub(crate) fn one() -> Self {
+    pub fn one() -> Self {
         Self(R)
     }
 
@@ -78,15 +78,22 @@ impl Base {
         Self(double(self.0, MODULUS))
     }
 
-    pub(crate) fn square(self) -> Self {
+    pub fn square(self) -> Self {
         Self(square(self.0, MODULUS, INV))
     }
 
-    pub(crate) fn invert(self) -> Option<Self> {
-        match invert(self.0, little_fermat(MODULUS), R, MODULUS, INV) {
-            Some(x) => Some(Self(x)),
-            None => None,
-        }
+    /// multiplicative inverse, `None` for zero
+    ///
+    /// ```
+    /// use jubjub::Base;
+    ///
+    /// let a = Base::from(7);
+    /// assert_eq!(a * a.invert().unwrap(), Base::one());
+    /// assert_eq!(a.square(), a * a);
+    /// assert!(Base::zero().invert().is_none());
+    /// ```
+    pub fn invert(self) -> Option<Self> {
+        invert(self.0, little_fermat(MODULUS), R, MODULUS, INV).map(Self)
     }
 
     pub fn to_bytes(self) -> [u8; 32] {
@@ -119,7 +126,7 @@ impl Base {
         }
     }
 
-    pub fn from_bytes_wide(bytes: &[u8; 64]) -> Self {
+    pub(crate) fn from_bytes_wide(bytes: &[u8; 64]) -> Self {
         Self(from_u512(
             [
                 u64::from_le_bytes(<[u8; 8]>::try_from(&bytes[0..8]).unwrap()),
@@ -138,26 +145,57 @@ impl Base {
         ))
     }
 
-    pub fn pow_vartime(&self, by: &[u64; 4]) -> Self {
+    /// `self^by` for a little endian exponent in constant time
+    ///
+    /// ```
+    /// use jubjub::Base;
+    ///
+    /// let a = Base::from(3);
+    /// assert_eq!(a.pow(&[5, 0, 0, 0]), Base::from(243));
+    /// assert_eq!(a.pow(&[0; 4]), Base::one());
+    /// ```
+    pub fn pow(&self, by: &[u64; 4]) -> Self {
+        let mut res = Self::one();
+        for e in by.iter().rev() {
+            for i in (0..64).rev() {
+                res = res.square();
+                let product = res * *self;
+                res = Self::select(res, product, ((*e >> i) & 1).wrapping_neg());
+            }
+        }
+        res
+    }
+
+    // `b` if every bit of `mask` is set, `a` if it's zero, without branching
+    fn select(a: Self, b: Self, mask: u64) -> Self {
+        Self([
+            a.0[0] ^ (mask & (a.0[0] ^ b.0[0])),
+            a.0[1] ^ (mask & (a.0[1] ^ b.0[1])),
+            a.0[2] ^ (mask & (a.0[2] ^ b.0[2])),
+            a.0[3] ^ (mask & (a.0[3] ^ b.0[3])),
+        ])
+    }
+
+    pub(crate) fn pow_vartime(&self, by: &[u64; 4]) -> Self {
         let mut res = Self::one();
         for e in by.iter().rev() {
             for i in (0..64).rev() {
                 res = res.square();
 
                 if ((*e >> i) & 1) == 1 {
-                    res = res * *self;
+                    res *= *self;
                 }
             }
         }
         res
     }
 
-    pub fn is_odd(self) -> bool {
+    pub(crate) fn is_odd(self) -> bool {
         let raw = self.to_raw();
-        (raw[0] % 2) != 0
+        raw[0] & 1 == 1
     }
 
-    pub fn sqrt(&self) -> Option<Self> {
+    pub(crate) fn sqrt(&self) -> Option<Self> {
         let w = self.pow_vartime(&[
             0x7fff2dff7fffffff,
             0x04d0ec02a9ded201,
@@ -188,10 +226,10 @@ impl Base {
             }
 
             if b != Self::one() {
-                x = x * z
+                x *= z
             };
             z = z.square();
-            b = b * z;
+            b *= z;
             v = k;
         }
 
@@ -203,6 +241,12 @@ impl Base {
     }
 }
 
+impl From<u64> for Base {
+    fn from(value: u64) -> Self {
+        Self::to_mont([value, 0, 0, 0])
+    }
+}
+
 impl Add for Base {
     type Output = Self;
 
@@ -211,6 +255,12 @@ impl Add for Base {
     }
 }
 
+impl AddAssign for Base {
+    fn add_assign(&mut self, rhs: Self) {
+        *self = *self + rhs
+    }
+}
+
 impl Neg for Base {
     type Output = Self;
 
@@ -235,6 +285,12 @@ impl Mul<Self> for Base {
     }
 }
 
+impl MulAssign for Base {
+    fn mul_assign(&mut self, rhs: Self) {
+        *self = *self * rhs
+    }
+}
+
 impl Debug for Base {
     fn fmt(&self, f: &mut Formatter) -> Result {
         write!(f, "0x")?;
@@ -271,6 +327,17 @@ mod tests {
         }
     }
 
+    proptest! {
+        #![proptest_config(ProptestConfig::with_cases(100))]
+        #[test]
+        fn test_pow(a in arb_field(), b in arb_field()) {
+            let by = b.to_raw();
+
+            assert_eq!(a.pow(&by), a.pow_vartime(&by));
+            assert_eq!(a.pow(&[2, 0, 0, 0]), a.square())
+        }
+    }
+
     proptest! {
         #![proptest_config(ProptestConfig::with_cases(100))]
         #[test]
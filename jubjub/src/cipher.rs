@@ -1,4 +1,16 @@
-use crate::{affine::Affine, base::Base, extend::Extended};
+//! ElGamal encryption over the prime order subgroup
+//!
+//! Point ElGamal encrypts `M` as `(r * G, M + r * Y)` and exponential ElGamal
+//! encrypts `v` as the point `v * G`, which makes ciphertexts additively
+//! homomorphic in `v` at the cost of a discrete logarithm on decryption.
+extern crate alloc;
+
+use core::ops::{Add, AddAssign, Neg, Sub, SubAssign};
+
+use crate::{affine::Affine, base::Base, extend::Extended, scalar::Scalar};
+
+use alloc::vec::Vec;
+use rand_core::RngCore;
 
 const BASE_X: Base = Base::to_mont([
     0x47bf46920a95a753,
@@ -41,12 +53,282 @@ impl Extended {
     }
 }
 
+#[derive(Clone, Copy, Debug)]
+pub struct SecretKey(Scalar);
+
+impl SecretKey {
+    pub fn new(value: Scalar) -> Self {
+        Self(value)
+    }
+
+    pub fn random(rand: impl RngCore) -> Self {
+        Self(Scalar::random(rand))
+    }
+
+    pub fn to_public_key(self) -> PublicKey {
+        PublicKey(Extended::basepoint() * self.0)
+    }
+
+    /// `M = C_2 - x * C_1`
+    pub fn decrypt(&self, ciphertext: Ciphertext) -> Extended {
+        ciphertext.c2 - ciphertext.c1 * self.0
+    }
+
+    /// recover `v` from `v * G`, `None` if it lies outside of the table bound
+    pub fn decrypt_value(&self, ciphertext: Ciphertext, table: &DiscreteLog) -> Option<u64> {
+        table.solve(self.decrypt(ciphertext))
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct PublicKey(Extended);
+
+impl PublicKey {
+    /// `None` unless the point lies in the prime order subgroup
+    pub fn new(point: Extended) -> Option<Self> {
+        point.is_torsion_free().then_some(Self(point))
+    }
+
+    pub fn to_point(self) -> Extended {
+        self.0
+    }
+
+    pub fn encrypt(&self, message: Extended, rand: impl RngCore) -> Ciphertext {
+        self.encrypt_with(message, Scalar::random(rand))
+    }
+
+    pub fn encrypt_value(&self, value: u64, rand: impl RngCore) -> Ciphertext {
+        self.encrypt(Extended::basepoint() * Scalar::from(value), rand)
+    }
+
+    /// `(r * G, M + r * Y)` with caller supplied randomness
+    pub fn encrypt_with(&self, message: Extended, r: Scalar) -> Ciphertext {
+        Ciphertext {
+            c1: Extended::basepoint() * r,
+            c2: message + self.0 * r,
+        }
+    }
+
+    /// add an encryption of identity, unlinkable to the original ciphertext
+    pub fn rerandomize(&self, ciphertext: Ciphertext, rand: impl RngCore) -> Ciphertext {
+        ciphertext + self.encrypt(Extended::identity(), rand)
+    }
+}
+
+/// ciphertext `(C_1, C_2)`
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Ciphertext {
+    pub c1: Extended,
+    pub c2: Extended,
+}
+
+impl Ciphertext {
+    pub fn to_bytes(self) -> [u8; 64] {
+        let mut bytes = [0; 64];
+        bytes[..32].copy_from_slice(&self.c1.to_bytes());
+        bytes[32..].copy_from_slice(&self.c2.to_bytes());
+        bytes
+    }
+
+    /// `None` unless both points lie in the prime order subgroup
+    pub fn from_bytes(bytes: [u8; 64]) -> Option<Self> {
+        let c1 = Affine::from_bytes(bytes[..32].try_into().unwrap())?.extend();
+        let c2 = Affine::from_bytes(bytes[32..].try_into().unwrap())?.extend();
+        (c1.is_torsion_free() && c2.is_torsion_free()).then_some(Self { c1, c2 })
+    }
+}
+
+impl Add for Ciphertext {
+    type Output = Self;
+
+    fn add(self, rhs: Self) -> Self {
+        Self {
+            c1: self.c1 + rhs.c1,
+            c2: self.c2 + rhs.c2,
+        }
+    }
+}
+
+impl AddAssign for Ciphertext {
+    fn add_assign(&mut self, rhs: Self) {
+        *self = *self + rhs
+    }
+}
+
+impl Sub for Ciphertext {
+    type Output = Self;
+
+    fn sub(self, rhs: Self) -> Self {
+        Self {
+            c1: self.c1 - rhs.c1,
+            c2: self.c2 - rhs.c2,
+        }
+    }
+}
+
+impl SubAssign for Ciphertext {
+    fn sub_assign(&mut self, rhs: Self) {
+        *self = *self - rhs
+    }
+}
+
+impl Neg for Ciphertext {
+    type Output = Self;
+
+    fn neg(self) -> Self {
+        Self {
+            c1: -self.c1,
+            c2: -self.c2,
+        }
+    }
+}
+
+/// baby-step giant-step table solving `v * G = P` for `v < bound`
+#[derive(Clone, Debug)]
+pub struct DiscreteLog {
+    // sorted (j * G, j) for j < step
+    baby_steps: Vec<([u8; 32], u64)>,
+    step: u64,
+    bound: u64,
+}
+
+impl DiscreteLog {
+    pub fn new(bound: u64) -> Self {
+        let step = isqrt(bound) + 1;
+        let mut point = Extended::identity();
+        let mut baby_steps: Vec<([u8; 32], u64)> = (0..step)
+            .map(|j| {
+                let entry = (point.to_bytes(), j);
+                point += Extended::basepoint();
+                entry
+            })
+            .collect();
+        baby_steps.sort_unstable();
+
+        Self {
+            baby_steps,
+            step,
+            bound,
+        }
+    }
+
+    pub fn bound(&self) -> u64 {
+        self.bound
+    }
+
+    pub fn solve(&self, point: Extended) -> Option<u64> {
+        let giant_step = -(Extended::basepoint() * Scalar::from(self.step));
+        let mut point = point;
+        for i in 0..self.step {
+            let bytes = point.to_bytes();
+            if let Ok(index) = self
+                .baby_steps
+                .binary_search_by(|(entry, _)| entry.cmp(&bytes))
+            {
+                let value = i * self.step + self.baby_steps[index].1;
+                return if value < self.bound {
+                    Some(value)
+                } else {
+                    None
+                };
+            }
+            point += giant_step;
+        }
+        None
+    }
+}
+
+// floor of the square root by bisection, `u64::isqrt` needs Rust 1.84
+fn isqrt(n: u64) -> u64 {
+    let (mut low, mut high) = (0u64, 1u64 << 32);
+    while high - low > 1 {
+        let mid = low + (high - low) / 2;
+        if mid * mid <= n {
+            low = mid;
+        } else {
+            high = mid;
+        }
+    }
+    low
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use proptest::{collection::vec, prelude::*};
+    use rand_core::OsRng;
+
+    prop_compose! {
+        fn arb_field()(
+            bytes in vec(any::<u8>(), 64)
+        ) -> Scalar {
+            Scalar::from_bytes_wide(&<[u8; 64]>::try_from(bytes).unwrap())
+        }
+    }
 
     #[test]
     fn test_basepoint() {
         assert_eq!(BASE_X * BASE_Y, BASE_T)
     }
+
+    #[test]
+    fn test_torsion_rejected() {
+        let order_two = Affine {
+            x: Base::zero(),
+            y: -Base::one(),
+        }
+        .extend();
+        let ciphertext = SecretKey::new(Scalar::one())
+            .to_public_key()
+            .encrypt(Extended::basepoint(), OsRng);
+        let mut bytes = ciphertext.to_bytes();
+        bytes[32..].copy_from_slice(&(ciphertext.c2 + order_two).to_bytes());
+
+        assert!(PublicKey::new(order_two).is_none());
+        assert!(PublicKey::new(Extended::basepoint() + order_two).is_none());
+        assert!(PublicKey::new(Extended::basepoint()).is_some());
+        assert!(Ciphertext::from_bytes(bytes).is_none())
+    }
+
+    #[test]
+    fn test_isqrt() {
+        for n in [0, 1, 2, 3, 4, 15, 16, 17, 999_999, 1_000_000, u64::MAX] {
+            let root = isqrt(n);
+            assert!(root * root <= n);
+            assert!(root == u32::MAX as u64 || (root + 1) * (root + 1) > n)
+        }
+    }
+
+    proptest! {
+        #![proptest_config(ProptestConfig::with_cases(20))]
+        #[test]
+        fn test_point_elgamal(x in arb_field(), m in arb_field()) {
+            let secret_key = SecretKey::new(x);
+            let public_key = secret_key.to_public_key();
+            let message = Extended::basepoint() * m;
+            let ciphertext = public_key.encrypt(message, OsRng);
+            let rerandomized = public_key.rerandomize(ciphertext, OsRng);
+
+            assert_eq!(secret_key.decrypt(ciphertext), message);
+            assert_eq!(secret_key.decrypt(rerandomized), message);
+            assert_ne!(rerandomized, ciphertext);
+            assert_eq!(Ciphertext::from_bytes(ciphertext.to_bytes()), Some(ciphertext))
+        }
+    }
+
+    proptest! {
+        #![proptest_config(ProptestConfig::with_cases(10))]
+        #[test]
+        fn test_exponential_elgamal(x in arb_field(), a in 0..500u64, b in 0..500u64) {
+            let table = DiscreteLog::new(1000);
+            let secret_key = SecretKey::new(x);
+            let public_key = secret_key.to_public_key();
+            let c_a = public_key.encrypt_value(a, OsRng);
+            let c_b = public_key.encrypt_value(b, OsRng);
+
+            assert_eq!(secret_key.decrypt_value(c_a + c_b, &table), Some(a + b));
+            assert_eq!(secret_key.decrypt_value(c_a + c_a - c_a, &table), Some(a));
+            assert_eq!(secret_key.decrypt_value(public_key.encrypt_value(1000, OsRng), &table), None)
+        }
+    }
 }
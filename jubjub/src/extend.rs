@@ -1,12 +1,17 @@
 //! Jubjub extend point
+extern crate alloc;
+
 use core::ops::{Add, AddAssign, Mul, Neg, Sub, SubAssign};
 
 use crate::affine::Affine;
 use crate::base::Base;
-use crate::coordinate::{add_mixed_point, add_projective_point, double_projective_point};
-use crate::limbs::Naf;
+use crate::coordinate::{
+    add_mixed_point, add_projective_point, double_projective_point, scalar_point,
+};
 use crate::scalar::Scalar;
 
+use alloc::vec;
+
 /// Jubjub extended coordinate
 #[derive(Clone, Copy, Debug)]
 pub struct Extended {
@@ -21,7 +26,7 @@ impl Extended {
         Self { x, y, t, z }
     }
 
-    pub(crate) fn identity() -> Self {
+    pub fn identity() -> Self {
         Self {
             x: Base::zero(),
             y: Base::one(),
@@ -38,15 +43,83 @@ impl Extended {
         }
     }
 
-    pub(crate) fn double(self) -> Self {
+    pub fn double(self) -> Self {
         double_projective_point(self)
     }
 
     pub fn to_bytes(self) -> [u8; 32] {
         self.to_affine().to_bytes()
     }
+
+    pub fn is_identity(self) -> bool {
+        self == Self::identity()
+    }
+
+    pub fn mul_by_cofactor(self) -> Self {
+        self.double().double().double()
+    }
+
+    /// point of order dividing the cofactor
+    pub fn is_small_order(self) -> bool {
+        self.mul_by_cofactor().is_identity()
+    }
+
+    /// point in the prime order subgroup, checked by r * P = (r - 1) * P + P
+    pub fn is_torsion_free(self) -> bool {
+        (self * -Scalar::one() + self).is_identity()
+    }
 }
 
+/// `sum(s_i * P_i)` with Pippenger's bucket method, panics on length mismatch
+pub fn multiscalar_mul(scalars: &[Scalar], points: &[Extended]) -> Extended {
+    assert_eq!(scalars.len(), points.len());
+    let c = match points.len() {
+        0..=31 => 3,
+        32..=255 => 5,
+        _ => 7,
+    };
+    let bytes: alloc::vec::Vec<[u8; 32]> = scalars.iter().map(|s| s.to_bytes()).collect();
+    let digit = |bytes: &[u8; 32], window: usize| {
+        (0..c).fold(0, |acc, i| {
+            let bit = window * c + i;
+            if bit < 256 {
+                acc | (((bytes[bit / 8] >> (bit % 8)) & 1) as usize) << i
+            } else {
+                acc
+            }
+        })
+    };
+
+    let mut result = Extended::identity();
+    for window in (0..256usize.div_ceil(c)).rev() {
+        (0..c).for_each(|_| result = result.double());
+
+        let mut buckets = vec![Extended::identity(); (1 << c) - 1];
+        for (bytes, point) in bytes.iter().zip(points) {
+            let digit = digit(bytes, window);
+            if digit != 0 {
+                buckets[digit - 1] += *point;
+            }
+        }
+
+        // sum(i * B_i) as running sums from the top bucket
+        let mut running = Extended::identity();
+        for bucket in buckets.into_iter().rev() {
+            running += bucket;
+            result += running;
+        }
+    }
+    result
+}
+
+impl PartialEq for Extended {
+    fn eq(&self, other: &Self) -> bool {
+        self.x * other.z == other.x * self.z && self.y * other.z == other.y * self.z
+    }
+}
+
+impl Eq for Extended {}
+
 impl Add<Extended> for Extended {
     type Output = Extended;
 
@@ -120,16 +193,7 @@ impl Mul<Scalar> for Extended {
     type Output = Extended;
 
     fn mul(self, scalar: Scalar) -> Extended {
-        let mut res = Extended::identity();
-        for naf in scalar.to_nafs().iter() {
-            res = double_projective_point(res);
-            if naf == &Naf::Plus {
-                res += self;
-            } else if naf == &Naf::Minus {
-                res -= self;
-            }
-        }
-        res
+        scalar_point(self, scalar)
     }
 }
 
@@ -165,4 +229,18 @@ mod tests {
             assert_eq!(additive.to_affine(), doubling.to_affine())
         }
     }
+
+    proptest! {
+        #![proptest_config(ProptestConfig::with_cases(10))]
+        #[test]
+        fn test_multiscalar_mul(pairs in vec((arb_field(), arb_point()), 0..40)) {
+            let (scalars, points): (alloc::vec::Vec<_>, alloc::vec::Vec<_>) = pairs.into_iter().unzip();
+            let naive = scalars
+                .iter()
+                .zip(points.iter())
+                .fold(Extended::identity(), |acc, (s, p)| acc + *p * *s);
+
+            assert_eq!(multiscalar_mul(&scalars, &points), naive)
+        }
+    }
 }
@@ -0,0 +1,216 @@
+//! Hash to curve through the birationally equivalent Montgomery curve
+//!
+//! Follows RFC 9380 with expand_message_xmd over BLAKE2b-512 and Elligator 2 on
+//! B * v^2 = u^3 + A * u^2 + u, where A = 40962 and B = -40964.
+extern crate alloc;
+
+use crate::affine::Affine;
+use crate::base::Base;
+use crate::extend::Extended;
+use crate::montgomery::{MontgomeryPoint, A, B};
+
+use alloc::vec::Vec;
+use blake2b_simd::Params;
+
+// BLAKE2b-512 block and output size
+const S_IN_BYTES: usize = 128;
+const B_IN_BYTES: usize = 64;
+
+// ceil((ceil(log2(p)) + k) / 8) with k = 128
+const L: usize = 48;
+
+// non-square of the base field
+const Z: u64 = 5;
+
+/// random oracle encoding, hash_to_field with two elements and cofactor clearing
+pub fn hash_to_curve(dst: &[u8], msg: &[u8]) -> Extended {
+    let u = hash_to_field::<2>(dst, msg);
+    let q0 = map_to_curve(u[0]);
+    let q1 = map_to_curve(u[1]);
+
+    (q0 + q1).mul_by_cofactor()
+}
+
+/// nonuniform encoding, hash_to_field with one element and cofactor clearing
+pub fn encode_to_curve(dst: &[u8], msg: &[u8]) -> Extended {
+    let u = hash_to_field::<1>(dst, msg);
+
+    map_to_curve(u[0]).extend().mul_by_cofactor()
+}
+
+pub fn hash_to_field<const N: usize>(dst: &[u8], msg: &[u8]) -> [Base; N] {
+    let uniform_bytes = expand_message_xmd(dst, msg, L * N);
+
+    let mut elements = [Base::zero(); N];
+    for (element, bytes) in elements.iter_mut().zip(uniform_bytes.chunks(L)) {
+        let mut wide = [0; 64];
+        wide[..L].copy_from_slice(bytes);
+        wide[..L].reverse();
+        *element = Base::from_bytes_wide(&wide);
+    }
+    elements
+}
+
+pub fn expand_message_xmd(dst: &[u8], msg: &[u8], len_in_bytes: usize) -> Vec<u8> {
+    let ell = len_in_bytes.div_ceil(B_IN_BYTES);
+    assert!(ell <= 255 && len_in_bytes <= 65535);
+
+    let oversize;
+    let dst = if dst.len() > 255 {
+        oversize = hash(&[b"H2C-OVERSIZE-DST-", dst]);
+        &oversize[..]
+    } else {
+        dst
+    };
+    let dst_len = [dst.len() as u8];
+
+    let b_0 = hash(&[
+        &[0; S_IN_BYTES],
+        msg,
+        &(len_in_bytes as u16).to_be_bytes(),
+        &[0],
+        dst,
+        &dst_len,
+    ]);
+    let mut b_i = hash(&[&b_0, &[1], dst, &dst_len]);
+
+    let mut uniform_bytes = Vec::with_capacity(ell * B_IN_BYTES);
+    uniform_bytes.extend_from_slice(&b_i);
+    for i in 2..=ell {
+        let mut xor = b_0;
+        xor.iter_mut().zip(b_i).for_each(|(a, b)| *a ^= b);
+        b_i = hash(&[&xor, &[i as u8], dst, &dst_len]);
+        uniform_bytes.extend_from_slice(&b_i);
+    }
+    uniform_bytes.truncate(len_in_bytes);
+    uniform_bytes
+}
+
+/// Elligator 2 followed by the rational map to twisted Edwards
+pub fn map_to_curve(u: Base) -> Affine {
+    let point = map_to_montgomery(u);
+    if point.v == Base::zero() {
+        // exceptional points map to identity
+        return Affine::identity();
+    }
+    point.to_affine()
+}
+
+/// Elligator 2 onto the Montgomery curve
+pub fn map_to_montgomery(u: Base) -> MontgomeryPoint {
+    let k = -Base::from(B);
+    let j_over_k = Base::from(A) * k.invert().unwrap();
+    let k2_inv = k.square().invert().unwrap();
+    let g = |x: Base| (x.square() + j_over_k * x + k2_inv) * x;
+
+    let mut x1 = -j_over_k * inv0(Base::one() + Base::from(Z) * u.square());
+    if x1 == Base::zero() {
+        x1 = -j_over_k;
+    }
+    let x2 = -x1 - j_over_k;
+
+    let (x, y) = match g(x1).sqrt() {
+        Some(y) => (x1, if y.is_odd() { y } else { -y }),
+        None => {
+            let y = g(x2).sqrt().unwrap();
+            (x2, if y.is_odd() { -y } else { y })
+        }
+    };
+
+    MontgomeryPoint {
+        u: x * k,
+        v: y * k,
+        infinity: false,
+    }
+}
+
+fn inv0(x: Base) -> Base {
+    x.invert().unwrap_or(Base::zero())
+}
+
+fn hash(inputs: &[&[u8]]) -> [u8; B_IN_BYTES] {
+    let mut state = Params::new().hash_length(B_IN_BYTES).to_state();
+    inputs.iter().for_each(|input| {
+        state.update(input);
+    });
+    *state.finalize().as_array()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use proptest::{collection::vec, prelude::*};
+
+    const DST_RO: &[u8] = b"QUUX-V01-CS02-with-jubjub_XMD:BLAKE2b_ELL2_RO_";
+    const DST_NU: &[u8] = b"QUUX-V01-CS02-with-jubjub_XMD:BLAKE2b_ELL2_NU_";
+
+    fn from_hex(hex: &str) -> Affine {
+        let mut bytes = [0; 32];
+        for (i, byte) in bytes.iter_mut().enumerate() {
+            *byte = u8::from_str_radix(&hex[2 * i..2 * i + 2], 16).unwrap();
+        }
+        Affine::from_bytes(bytes).unwrap()
+    }
+
+    // regression vectors cross-checked against a Python model of this
+    // suite, not conformance vectors: none are published for Jubjub
+    #[test]
+    fn test_hash_to_curve_regression() {
+        let long = [b'a'; 512];
+        let vectors: [(&[u8], &str); 4] = [
+            (
+                b"",
+                "c222527c90184d9280925c3dbdf28a2a33d6f99edb589fc7327466386790a2dd",
+            ),
+            (
+                b"abc",
+                "cf9bf53b45ce77a4a83f4e76c606bc988b6bfd1175d9a7d5722bcaf46099f00f",
+            ),
+            (
+                b"abcdef0123456789",
+                "8106f625974874b486f05b2b8bef00be970974876313befee9db08fa2d3d8abe",
+            ),
+            (
+                &long,
+                "4ddf083a2bf5084bee1f01426202d7a4e0f093a4cd94d2eee7bd40b3109994db",
+            ),
+        ];
+
+        for (msg, expected) in vectors {
+            assert_eq!(hash_to_curve(DST_RO, msg).to_affine(), from_hex(expected))
+        }
+    }
+
+    #[test]
+    fn test_encode_to_curve_regression() {
+        let vectors: [(&[u8], &str); 2] = [
+            (
+                b"",
+                "682f877d5e24638c5e9a707f17957b55c959514aef5e25622f2de6d16a67300b",
+            ),
+            (
+                b"abc",
+                "0e783a3e02ff5df2517d1500babb6c51b11ccc3f9c6d3a627118bceedf9ad1c0",
+            ),
+        ];
+
+        for (msg, expected) in vectors {
+            assert_eq!(encode_to_curve(DST_NU, msg).to_affine(), from_hex(expected))
+        }
+    }
+
+    proptest! {
+        #![proptest_config(ProptestConfig::with_cases(20))]
+        #[test]
+        fn test_hash_to_curve(msg in vec(any::<u8>(), 0..100)) {
+            let u = hash_to_field::<1>(DST_RO, &msg);
+            let point = hash_to_curve(DST_RO, &msg);
+
+            assert!(map_to_montgomery(u[0]).is_on_curve());
+            assert!(map_to_curve(u[0]).is_on_curve());
+            assert!(point.to_affine().is_on_curve());
+            assert!(point.is_torsion_free());
+            assert!(!point.is_identity())
+        }
+    }
+}
@@ -1,9 +1,17 @@
 #![no_std]
 pub mod affine;
 mod base;
-mod cipher;
+pub mod cipher;
 mod coordinate;
 pub mod extend;
+pub mod hash_to_curve;
 mod limbs;
 mod math;
+pub mod montgomery;
+pub mod pedersen;
+pub mod pedersen_hash;
+pub mod poseidon;
 pub mod scalar;
+
+/// base field element, the input of Poseidon and the coordinates of points
+pub use base::Base;
@@ -0,0 +1,227 @@
+//! Jubjub Montgomery form `B * v^2 = u^3 + A * u^2 + u`
+//!
+//! Birationally equivalent to the twisted Edwards form with A = 40962 and
+//! B = -40964 through `(u, v) = ((1 + y) / (1 - y), u / x)`.
+use core::ops::Mul;
+
+use crate::affine::Affine;
+use crate::base::Base;
+use crate::extend::Extended;
+use crate::scalar::Scalar;
+
+pub(crate) const A: u64 = 40962;
+pub(crate) const B: u64 = 40964;
+
+// (A - 2) / 4
+const A24: u64 = 10240;
+
+/// Montgomery affine point, the identity is the point at infinity
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct MontgomeryPoint {
+    pub(crate) u: Base,
+    pub(crate) v: Base,
+    pub(crate) infinity: bool,
+}
+
+impl MontgomeryPoint {
+    pub fn new(u: Base, v: Base) -> Option<Self> {
+        let point = Self {
+            u,
+            v,
+            infinity: false,
+        };
+        if point.is_on_curve() {
+            Some(point)
+        } else {
+            None
+        }
+    }
+
+    pub fn identity() -> Self {
+        Self {
+            u: Base::zero(),
+            v: Base::zero(),
+            infinity: true,
+        }
+    }
+
+    pub fn is_identity(self) -> bool {
+        self.infinity
+    }
+
+    pub fn is_on_curve(self) -> bool {
+        self.infinity
+            || -Base::from(B) * self.v.square()
+                == (self.u.square() + Base::from(A) * self.u + Base::one()) * self.u
+    }
+
+    pub fn u(self) -> Base {
+        self.u
+    }
+
+    pub fn v(self) -> Base {
+        self.v
+    }
+
+    pub fn to_affine(self) -> Affine {
+        Affine::from(self)
+    }
+
+    pub fn to_extended(self) -> Extended {
+        Affine::from(self).extend()
+    }
+}
+
+impl From<Affine> for MontgomeryPoint {
+    fn from(point: Affine) -> Self {
+        let one_minus_y = Base::one() - point.y;
+        match one_minus_y.invert() {
+            // identity (0, 1)
+            None => Self::identity(),
+            Some(inv) => {
+                let u = (Base::one() + point.y) * inv;
+                match point.x.invert() {
+                    Some(x_inv) => Self {
+                        u,
+                        v: u * x_inv,
+                        infinity: false,
+                    },
+                    // order two point (0, -1) maps to (0, 0)
+                    None => Self {
+                        u: Base::zero(),
+                        v: Base::zero(),
+                        infinity: false,
+                    },
+                }
+            }
+        }
+    }
+}
+
+impl From<Extended> for MontgomeryPoint {
+    fn from(point: Extended) -> Self {
+        Self::from(point.to_affine())
+    }
+}
+
+impl From<MontgomeryPoint> for Affine {
+    fn from(point: MontgomeryPoint) -> Self {
+        if point.infinity {
+            return Affine::identity();
+        }
+        match (point.v.invert(), (point.u + Base::one()).invert()) {
+            (Some(v_inv), Some(u_plus_one_inv)) => Affine {
+                x: point.u * v_inv,
+                y: (point.u - Base::one()) * u_plus_one_inv,
+            },
+            // (0, 0) maps to (0, -1)
+            _ => Affine {
+                x: Base::zero(),
+                y: -Base::one(),
+            },
+        }
+    }
+}
+
+impl Mul<Scalar> for MontgomeryPoint {
+    type Output = MontgomeryPoint;
+
+    fn mul(self, scalar: Scalar) -> MontgomeryPoint {
+        MontgomeryPoint::from(self.to_affine() * scalar)
+    }
+}
+
+/// x-only Montgomery ladder, `None` for the point at infinity
+pub fn ladder(u: Base, scalar: Scalar) -> Option<Base> {
+    let a24 = Base::from(A24);
+    let (mut x2, mut z2) = (Base::one(), Base::zero());
+    let (mut x3, mut z3) = (u, Base::one());
+    let mut swap = false;
+
+    let raw = scalar.to_raw();
+    for i in (0..256).rev() {
+        let bit = (raw[i / 64] >> (i % 64)) & 1 == 1;
+        swap ^= bit;
+        cswap(swap, &mut x2, &mut x3);
+        cswap(swap, &mut z2, &mut z3);
+        swap = bit;
+
+        let a = x2 + z2;
+        let aa = a.square();
+        let b = x2 - z2;
+        let bb = b.square();
+        let e = aa - bb;
+        let c = x3 + z3;
+        let d = x3 - z3;
+        let da = d * a;
+        let cb = c * b;
+        x3 = (da + cb).square();
+        z3 = u * (da - cb).square();
+        x2 = aa * bb;
+        z2 = e * (aa + a24 * e);
+    }
+    cswap(swap, &mut x2, &mut x3);
+    cswap(swap, &mut z2, &mut z3);
+
+    z2.invert().map(|z_inv| x2 * z_inv)
+}
+
+fn cswap(swap: bool, a: &mut Base, b: &mut Base) {
+    let mask = 0u64.wrapping_sub(swap as u64);
+    for (a, b) in a.0.iter_mut().zip(b.0.iter_mut()) {
+        let t = mask & (*a ^ *b);
+        *a ^= t;
+        *b ^= t;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use proptest::{collection::vec, prelude::*};
+
+    prop_compose! {
+        fn arb_field()(
+            bytes in vec(any::<u8>(), 64)
+        ) -> Scalar {
+            Scalar::from_bytes_wide(&<[u8; 64]>::try_from(bytes).unwrap())
+        }
+    }
+
+    #[test]
+    fn test_exceptional_points() {
+        let order_two = Affine {
+            x: Base::zero(),
+            y: -Base::one(),
+        };
+
+        assert!(MontgomeryPoint::from(Affine::identity()).is_identity());
+        assert_eq!(MontgomeryPoint::from(order_two).u(), Base::zero());
+        assert_eq!(MontgomeryPoint::from(order_two).to_affine(), order_two);
+        assert_eq!(MontgomeryPoint::identity().to_affine(), Affine::identity());
+    }
+
+    proptest! {
+        #![proptest_config(ProptestConfig::with_cases(100))]
+        #[test]
+        fn test_conversion(r in arb_field()) {
+            let point = (Affine::basepoint() * r).to_affine();
+            let montgomery = MontgomeryPoint::from(point);
+
+            assert!(montgomery.is_on_curve());
+            assert_eq!(montgomery.to_affine(), point)
+        }
+    }
+
+    proptest! {
+        #![proptest_config(ProptestConfig::with_cases(20))]
+        #[test]
+        fn test_ladder(r in arb_field(), k in arb_field()) {
+            let point = MontgomeryPoint::from(Affine::generator() * r);
+            let product = point * k;
+
+            assert_eq!(ladder(point.u(), k), Some(product.u()));
+            assert_eq!(ladder(point.u(), Scalar::zero()), None)
+        }
+    }
+}
@@ -0,0 +1,259 @@
+//! Pedersen commitments with NUMS generators
+extern crate alloc;
+
+use core::ops::{Add, AddAssign, Neg, Sub, SubAssign};
+
+use crate::affine::Affine;
+use crate::extend::Extended;
+use crate::hash_to_curve::hash_to_curve;
+use crate::scalar::Scalar;
+
+use alloc::vec::Vec;
+
+const PEDERSEN_DST: &[u8] = b"MultiRedDSA-V01-CS01-with-jubjub_XMD:BLAKE2b_ELL2_RO_Pedersen";
+
+// reserved for the blinding generator, out of reach of caller labels
+const BLINDING_DST: &[u8] =
+    b"MultiRedDSA-V01-CS01-with-jubjub_XMD:BLAKE2b_ELL2_RO_PedersenBlinding";
+
+/// derive generator from label, nobody knows its discrete logarithm
+///
+/// hashes `len(label) (u64 le) || label || index (u32 le)`, so distinct
+/// `(label, index)` pairs never share an input
+pub fn nums_generator(label: &[u8], index: u32) -> Extended {
+    let mut msg = Vec::with_capacity(label.len() + 12);
+    msg.extend_from_slice(&(label.len() as u64).to_le_bytes());
+    msg.extend_from_slice(label);
+    msg.extend_from_slice(&index.to_le_bytes());
+    hash_to_curve(PEDERSEN_DST, &msg)
+}
+
+// `H`, under its own domain so no labelled generator can equal it
+fn blinding_generator() -> Extended {
+    hash_to_curve(BLINDING_DST, &[])
+}
+
+/// commitment `C = v * G + r * H`
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Commitment(pub(crate) Extended);
+
+impl Commitment {
+    pub fn new(point: Extended) -> Self {
+        Self(point)
+    }
+
+    pub fn to_point(self) -> Extended {
+        self.0
+    }
+
+    pub fn to_bytes(self) -> [u8; 32] {
+        self.0.to_bytes()
+    }
+
+    /// `None` unless the point is in the prime order subgroup, so a
+    /// commitment can't be mauled by adding a torsion point
+    pub fn from_bytes(bytes: [u8; 32]) -> Option<Self> {
+        let point = Affine::from_bytes(bytes)?.extend();
+        if point.is_torsion_free() {
+            Some(Self(point))
+        } else {
+            None
+        }
+    }
+}
+
+impl Add for Commitment {
+    type Output = Self;
+
+    fn add(self, rhs: Self) -> Self {
+        Self(self.0 + rhs.0)
+    }
+}
+
+impl AddAssign for Commitment {
+    fn add_assign(&mut self, rhs: Self) {
+        *self = *self + rhs
+    }
+}
+
+impl Sub for Commitment {
+    type Output = Self;
+
+    fn sub(self, rhs: Self) -> Self {
+        Self(self.0 - rhs.0)
+    }
+}
+
+impl SubAssign for Commitment {
+    fn sub_assign(&mut self, rhs: Self) {
+        *self = *self - rhs
+    }
+}
+
+impl Neg for Commitment {
+    type Output = Self;
+
+    fn neg(self) -> Self {
+        Self(-self.0)
+    }
+}
+
+/// value generator `G` and blinding generator `H`
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct PedersenGens {
+    pub value: Extended,
+    pub blinding: Extended,
+}
+
+impl Default for PedersenGens {
+    fn default() -> Self {
+        Self {
+            value: Extended::basepoint(),
+            blinding: blinding_generator(),
+        }
+    }
+}
+
+impl PedersenGens {
+    pub fn commit(&self, value: Scalar, blinding: Scalar) -> Commitment {
+        Commitment(self.value * value + self.blinding * blinding)
+    }
+
+    pub fn open(&self, commitment: Commitment, value: Scalar, blinding: Scalar) -> bool {
+        self.commit(value, blinding) == commitment
+    }
+}
+
+/// generators `G_1, ..., G_n` and blinding generator `H`
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct VectorGens {
+    pub values: Vec<Extended>,
+    pub blinding: Extended,
+}
+
+impl VectorGens {
+    pub fn new(n: usize) -> Self {
+        Self::with_label(b"vector", n)
+    }
+
+    pub fn with_label(label: &[u8], n: usize) -> Self {
+        Self {
+            values: (0..n as u32).map(|i| nums_generator(label, i)).collect(),
+            blinding: blinding_generator(),
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.values.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.values.is_empty()
+    }
+
+    /// `C = sum(v_i * G_i) + r * H`, panics if there are more values than generators
+    pub fn commit(&self, values: &[Scalar], blinding: Scalar) -> Commitment {
+        assert!(values.len() <= self.values.len());
+        let point = values
+            .iter()
+            .zip(self.values.iter())
+            .fold(self.blinding * blinding, |acc, (v, g)| acc + *g * *v);
+        Commitment(point)
+    }
+
+    pub fn open(&self, commitment: Commitment, values: &[Scalar], blinding: Scalar) -> bool {
+        values.len() <= self.values.len() && self.commit(values, blinding) == commitment
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use proptest::{collection::vec, prelude::*};
+
+    prop_compose! {
+        fn arb_field()(
+            bytes in vec(any::<u8>(), 64)
+        ) -> Scalar {
+            Scalar::from_bytes_wide(&<[u8; 64]>::try_from(bytes).unwrap())
+        }
+    }
+
+    #[test]
+    fn test_generators() {
+        let gens = PedersenGens::default();
+        let vector = VectorGens::new(4);
+
+        assert!(gens.blinding.is_torsion_free());
+        assert_ne!(gens.value, gens.blinding);
+        assert!(vector.values.iter().all(|g| g.is_torsion_free()));
+        assert_ne!(vector.values[0], vector.values[1]);
+        assert_eq!(vector, VectorGens::new(4));
+    }
+
+    #[test]
+    fn test_blinding_unreachable() {
+        let blinding = PedersenGens::default().blinding;
+        let labels: [&[u8]; 4] = [b"", b"blinding", b"vector", b"blinding\x00\x00\x00\x00"];
+
+        for label in labels {
+            let gens = VectorGens::with_label(label, 4);
+            assert_eq!(gens.blinding, blinding);
+            assert!(gens
+                .values
+                .iter()
+                .all(|g| *g != blinding && *g != Extended::basepoint()));
+        }
+        assert_ne!(
+            nums_generator(b"a", 0),
+            nums_generator(b"", u32::from_le_bytes(*b"a\0\0\0"))
+        );
+    }
+
+    #[test]
+    fn test_torsion_rejected() {
+        let gens = PedersenGens::default();
+        let commitment = gens.commit(Scalar::from(7), Scalar::from(11));
+        // order two point (0, -1)
+        let order_two = Affine {
+            x: crate::base::Base::zero(),
+            y: -crate::base::Base::one(),
+        };
+
+        assert!(Commitment::from_bytes(order_two.to_bytes()).is_none());
+        assert!(Commitment::from_bytes((commitment.0 + order_two).to_bytes()).is_none());
+        assert_eq!(
+            Commitment::from_bytes(commitment.to_bytes()),
+            Some(commitment)
+        );
+    }
+
+    proptest! {
+        #![proptest_config(ProptestConfig::with_cases(20))]
+        #[test]
+        fn test_homomorphism(a in arb_field(), b in arb_field(), r in arb_field(), s in arb_field()) {
+            let gens = PedersenGens::default();
+            let c_a = gens.commit(a, r);
+            let c_b = gens.commit(b, s);
+
+            assert!(gens.open(c_a + c_b, a + b, r + s));
+            assert!(gens.open(c_a - c_b, a - b, r - s));
+            assert!(!gens.open(c_a, a, s));
+            assert_eq!(Commitment::from_bytes(c_a.to_bytes()), Some(c_a))
+        }
+    }
+
+    proptest! {
+        #![proptest_config(ProptestConfig::with_cases(10))]
+        #[test]
+        fn test_vector_commitment(a in arb_field(), b in arb_field(), r in arb_field(), s in arb_field()) {
+            let gens = VectorGens::new(2);
+            let c_1 = gens.commit(&[a, b], r);
+            let c_2 = gens.commit(&[b, a], s);
+
+            assert!(gens.open(c_1 + c_2, &[a + b, a + b], r + s));
+            assert!(!gens.open(c_1, &[b, a], r));
+            assert!(!gens.open(c_1, &[a, b, a], r))
+        }
+    }
+}
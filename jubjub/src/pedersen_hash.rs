@@ -0,0 +1,312 @@
+//! Sapling windowed Pedersen hash
+//!
+//! The input is split into 3 bit chunks `(s0, s1, s2)` encoded as
+//! `(1 - 2 * s2) * (1 + s0 + 2 * s1)`, and every segment of 63 chunks is
+//! multiplied by its own generator. The tables hold `enc * 2^(4 * j) * I_i`
+//! for each chunk position so hashing costs one addition per chunk.
+extern crate alloc;
+
+use crate::affine::Affine;
+use crate::base::Base;
+use crate::extend::Extended;
+
+use alloc::vec::Vec;
+use blake2s_simd::Params;
+
+/// first 64 bytes of the BLAKE2s input for group hash
+pub const GH_FIRST_BLOCK: &[u8; 64] =
+    b"096b36a5804bfacef1691e173c366a47ff5ba84a44f26ddd7e8d9f79d5b42df0";
+
+pub const PEDERSEN_HASH_PERSONAL: &[u8; 8] = b"Zcash_PH";
+
+const CHUNKS_PER_SEGMENT: usize = 63;
+const SEGMENTS: usize = 6;
+
+/// GroupHash^J(tag), the prime order point or `None` for the next tag
+pub fn group_hash(tag: &[u8], personal: &[u8; 8]) -> Option<Extended> {
+    let digest = Params::new()
+        .hash_length(32)
+        .personal(personal)
+        .to_state()
+        .update(GH_FIRST_BLOCK)
+        .update(tag)
+        .finalize();
+
+    let point = Affine::from_bytes(*digest.as_array())?
+        .extend()
+        .mul_by_cofactor();
+    if point.is_identity() {
+        None
+    } else {
+        Some(point)
+    }
+}
+
+/// FindGroupHash^J(tag), appends a counter byte until group hash succeeds
+pub fn find_group_hash(tag: &[u8], personal: &[u8; 8]) -> Extended {
+    let mut tag = tag.to_vec();
+    tag.push(0);
+    loop {
+        if let Some(point) = group_hash(&tag, personal) {
+            return point;
+        }
+        let counter = tag.last_mut().unwrap();
+        *counter = counter.checked_add(1).expect("group hash counter overflow");
+    }
+}
+
+/// prefix bits of the Sapling hash instances
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Personalization {
+    NoteCommitment,
+    MerkleTree(usize),
+}
+
+impl Personalization {
+    /// `None` for a Merkle tree depth that doesn't fit in six bits below `111111`
+    pub fn bits(self) -> Option<[bool; 6]> {
+        match self {
+            Personalization::NoteCommitment => Some([true; 6]),
+            Personalization::MerkleTree(depth) if depth < 63 => {
+                Some(core::array::from_fn(|i| (depth >> i) & 1 == 1))
+            }
+            Personalization::MerkleTree(_) => None,
+        }
+    }
+}
+
+/// precomputed window tables for the six Sapling generators
+#[derive(Clone, Debug)]
+pub struct PedersenHasher {
+    // tables[segment][chunk][bits]
+    tables: Vec<Vec<[Extended; 8]>>,
+}
+
+impl Default for PedersenHasher {
+    fn default() -> Self {
+        let tables = (0..SEGMENTS as u32)
+            .map(|i| {
+                let generator = find_group_hash(&i.to_le_bytes(), PEDERSEN_HASH_PERSONAL);
+                window_table(generator)
+            })
+            .collect();
+
+        Self { tables }
+    }
+}
+
+impl PedersenHasher {
+    /// PedersenHash(personalization || bits), `None` for an invalid
+    /// personalization or an input beyond the six Sapling generators
+    pub fn hash(
+        &self,
+        personalization: Personalization,
+        bits: impl IntoIterator<Item = bool>,
+    ) -> Option<Extended> {
+        let mut bits = personalization.bits()?.into_iter().chain(bits).peekable();
+        let mut result = Extended::identity();
+        let mut chunk = 0;
+
+        while bits.peek().is_some() {
+            let segment = chunk / CHUNKS_PER_SEGMENT;
+            if segment >= SEGMENTS {
+                return None;
+            }
+
+            let s0 = bits.next().unwrap_or(false) as usize;
+            let s1 = bits.next().unwrap_or(false) as usize;
+            let s2 = bits.next().unwrap_or(false) as usize;
+            result += self.tables[segment][chunk % CHUNKS_PER_SEGMENT][s0 | s1 << 1 | s2 << 2];
+            chunk += 1;
+        }
+
+        Some(result)
+    }
+
+    /// Extract_J(PedersenHash(..)), the u coordinate of the hash
+    pub fn hash_to_u(
+        &self,
+        personalization: Personalization,
+        bits: impl IntoIterator<Item = bool>,
+    ) -> Option<Base> {
+        Some(self.hash(personalization, bits)?.to_affine().x)
+    }
+
+    /// MerkleCRH^Sapling(layer, left, right) over 255 bit little endian u
+    /// coordinates, `None` if `depth` is 63 or more
+    pub fn merkle_hash(&self, depth: usize, left: &[u8; 32], right: &[u8; 32]) -> Option<[u8; 32]> {
+        let bits = le_bits(left).chain(le_bits(right));
+        Some(
+            self.hash_to_u(Personalization::MerkleTree(depth), bits)?
+                .to_bytes(),
+        )
+    }
+}
+
+fn le_bits(bytes: &[u8; 32]) -> impl Iterator<Item = bool> + '_ {
+    bytes
+        .iter()
+        .flat_map(|byte| (0..8).map(move |i| (byte >> i) & 1 == 1))
+        .take(255)
+}
+
+// table[j][s0 | s1 << 1 | s2 << 2] = enc(s0, s1, s2) * 2^(4j) * generator
+fn window_table(generator: Extended) -> Vec<[Extended; 8]> {
+    let mut base = generator;
+    (0..CHUNKS_PER_SEGMENT)
+        .map(|_| {
+            let multiples = [
+                base,
+                base.double(),
+                base.double() + base,
+                base.double().double(),
+            ];
+            let table = core::array::from_fn(|bits| {
+                let magnitude = multiples[bits & 0b11];
+                if bits & 0b100 == 0 {
+                    magnitude
+                } else {
+                    -magnitude
+                }
+            });
+            base = multiples[3].double().double();
+            table
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::scalar::Scalar;
+    use proptest::{collection::vec, prelude::*};
+
+    fn from_hex(hex: &str) -> [u8; 32] {
+        let mut bytes = [0; 32];
+        for (i, byte) in bytes.iter_mut().enumerate() {
+            *byte = u8::from_str_radix(&hex[2 * i..2 * i + 2], 16).unwrap();
+        }
+        bytes
+    }
+
+    // sum of enc(m_j) * 2^(4 * (j - 1)) * I_i with plain scalar multiplication
+    fn naive_hash(bits: &[bool]) -> Extended {
+        let scalar = |value: i64| {
+            let magnitude = Scalar::to_mont([value.unsigned_abs(), 0, 0, 0]);
+            if value < 0 {
+                -magnitude
+            } else {
+                magnitude
+            }
+        };
+        let sixteen = scalar(16);
+
+        let mut result = Extended::identity();
+        for (i, segment) in bits.chunks(3 * CHUNKS_PER_SEGMENT).enumerate() {
+            let generator = find_group_hash(&(i as u32).to_le_bytes(), PEDERSEN_HASH_PERSONAL);
+            let mut sum = Scalar::zero();
+            let mut power = Scalar::one();
+            for chunk in segment.chunks(3) {
+                let bit = |j: usize| chunk.get(j).copied().unwrap_or(false) as i64;
+                sum = sum + scalar((1 - 2 * bit(2)) * (1 + bit(0) + 2 * bit(1))) * power;
+                power = power * sixteen;
+            }
+            result += generator * sum;
+        }
+        result
+    }
+
+    #[test]
+    fn test_group_hash() {
+        // Sapling spend authorization and value commitment randomness generators
+        let spend_auth = find_group_hash(b"", b"Zcash_G_");
+        let binding = find_group_hash(b"r", b"Zcash_cv");
+
+        assert_eq!(
+            spend_auth.to_bytes(),
+            from_hex("30b5f2aaad325630bcdddbce4d67656d05fd1cc2d037bb5375b6e96d9e01a1d7")
+        );
+        assert_eq!(
+            binding.to_bytes(),
+            from_hex("8b6a0b38b9faae3c3b803b47b0f146ad50ab221e6e2afbe6dbde45cba9d381ed")
+        );
+    }
+
+    #[test]
+    fn test_empty_roots() {
+        // Sapling note commitment tree roots of empty subtrees
+        let hasher = PedersenHasher::default();
+        let expected = [
+            "817de36ab2d57feb077634bca77819c8e0bd298c04f6fed0e6a83cc1356ca155",
+            "ffe9fc03f18b176c998806439ff0bb8ad193afdb27b2ccbc88856916dd804e34",
+            "d8283386ef2ef07ebdbb4383c12a739a953a4d6e0d6fb1139a4036d693bfbb6c",
+        ];
+
+        let mut node = [0; 32];
+        node[0] = 1;
+        for (depth, root) in expected.iter().enumerate() {
+            node = hasher.merkle_hash(depth, &node, &node).unwrap();
+            assert_eq!(node, from_hex(root));
+        }
+    }
+
+    #[test]
+    fn test_hash_vectors() {
+        // zcash_primitives Sapling Pedersen hash test vectors, (u, v) big endian
+        let hasher = PedersenHasher::default();
+        let vectors: [(&[bool], &str, &str); 2] = [
+            (
+                &[],
+                "06b1187c11ca4fb4383b2e0d0dbbde3ad3617338b5029187ec65a5eaed5e4d0b",
+                "3ce70f536652f0dea496393a1e55c4e08b9d55508e16d11e5db40d4810cbc982",
+            ),
+            (
+                &[false],
+                "2fc3bc454c337f71d4f04f86304262fcbfc9ecd808716b92fc42cbe6827f7f1a",
+                "46d0d25bf1a654eedc6a9b1e5af398925113959feac31b7a2c036ff9b9ec0638",
+            ),
+        ];
+
+        for (bits, u, v) in vectors {
+            let point = hasher
+                .hash(Personalization::NoteCommitment, bits.iter().copied())
+                .unwrap()
+                .to_affine();
+            let (mut u, mut v) = (from_hex(u), from_hex(v));
+            u.reverse();
+            v.reverse();
+            assert_eq!(point.x.to_bytes(), u);
+            assert_eq!(point.y.to_bytes(), v);
+        }
+    }
+
+    #[test]
+    fn test_invalid_inputs() {
+        let hasher = PedersenHasher::default();
+        let capacity = SEGMENTS * CHUNKS_PER_SEGMENT * 3 - 6;
+
+        assert!(Personalization::MerkleTree(63).bits().is_none());
+        assert!(hasher.merkle_hash(63, &[0; 32], &[0; 32]).is_none());
+        assert!(hasher
+            .hash(Personalization::NoteCommitment, (0..capacity).map(|_| true))
+            .is_some());
+        assert!(hasher
+            .hash(
+                Personalization::NoteCommitment,
+                (0..=capacity).map(|_| true)
+            )
+            .is_none());
+    }
+
+    proptest! {
+        #![proptest_config(ProptestConfig::with_cases(10))]
+        #[test]
+        fn test_window_tables(bits in vec(any::<bool>(), 0..600)) {
+            let hasher = PedersenHasher::default();
+            let personalization = Personalization::MerkleTree(5);
+            let prefixed: Vec<bool> = personalization.bits().unwrap().into_iter().chain(bits.iter().copied()).collect();
+
+            assert_eq!(hasher.hash(personalization, bits), Some(naive_hash(&prefixed)))
+        }
+    }
+}
@@ -0,0 +1,343 @@
+//! Poseidon permutation and sponge over the Jubjub base field
+extern crate alloc;
+
+use crate::base::Base;
+
+use alloc::boxed::Box;
+use alloc::vec::Vec;
+use once_cell::race::OnceBox;
+
+// field and s-box descriptors of the reference parameter script
+const PRIME_FIELD: u16 = 1;
+const SBOX_POWER: u16 = 0;
+const FIELD_SIZE: u16 = 255;
+
+static WIDTH3: OnceBox<PoseidonParams> = OnceBox::new();
+static WIDTH5: OnceBox<PoseidonParams> = OnceBox::new();
+
+/// Poseidon instance with round constants and MDS matrix generated by the Grain LFSR
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PoseidonParams {
+    width: usize,
+    full_rounds: usize,
+    partial_rounds: usize,
+    round_constants: Vec<Base>,
+    mds: Vec<Vec<Base>>,
+}
+
+impl PoseidonParams {
+    pub fn new(width: usize, full_rounds: usize, partial_rounds: usize) -> Self {
+        let mut grain = Grain::new(
+            SBOX_POWER,
+            width as u16,
+            full_rounds as u16,
+            partial_rounds as u16,
+        );
+        let round_constants = (0..(full_rounds + partial_rounds) * width)
+            .map(|_| grain.field_element())
+            .collect();
+        let mds = grain.cauchy_matrix(width);
+
+        Self {
+            width,
+            full_rounds,
+            partial_rounds,
+            round_constants,
+            mds,
+        }
+    }
+
+    /// t = 3, 128 bit security, generated once
+    pub fn width3() -> &'static Self {
+        WIDTH3.get_or_init(|| Box::new(Self::new(3, 8, 57)))
+    }
+
+    /// t = 5, 128 bit security, generated once
+    pub fn width5() -> &'static Self {
+        WIDTH5.get_or_init(|| Box::new(Self::new(5, 8, 60)))
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn permute(&self, state: &mut [Base]) {
+        assert_eq!(state.len(), self.width);
+        let half = self.full_rounds / 2;
+
+        for (round, constants) in self.round_constants.chunks(self.width).enumerate() {
+            state.iter_mut().zip(constants).for_each(|(s, c)| *s += *c);
+
+            if round < half || round >= half + self.partial_rounds {
+                state.iter_mut().for_each(|s| *s = sbox(*s));
+            } else {
+                state[0] = sbox(state[0]);
+            }
+
+            let mixed: Vec<Base> = self
+                .mds
+                .iter()
+                .map(|row| {
+                    row.iter()
+                        .zip(state.iter())
+                        .fold(Base::zero(), |acc, (m, s)| acc + *m * *s)
+                })
+                .collect();
+            state.copy_from_slice(&mixed);
+        }
+    }
+}
+
+// x^5
+fn sbox(x: Base) -> Base {
+    x.square().square() * x
+}
+
+/// duplex sponge, the first element is capacity and the rest is rate
+#[derive(Clone, Debug)]
+pub struct Sponge<'a> {
+    params: &'a PoseidonParams,
+    state: Vec<Base>,
+    position: usize,
+    squeezing: bool,
+}
+
+impl<'a> Sponge<'a> {
+    pub fn new(params: &'a PoseidonParams, domain: Base) -> Self {
+        let mut state = alloc::vec![Base::zero(); params.width];
+        state[0] = domain;
+
+        Self {
+            params,
+            state,
+            position: 0,
+            squeezing: false,
+        }
+    }
+
+    pub fn absorb(&mut self, element: Base) {
+        if self.squeezing || self.position == self.rate() {
+            self.params.permute(&mut self.state);
+            self.position = 0;
+            self.squeezing = false;
+        }
+        self.state[1 + self.position] += element;
+        self.position += 1;
+    }
+
+    pub fn squeeze(&mut self) -> Base {
+        if !self.squeezing || self.position == self.rate() {
+            self.params.permute(&mut self.state);
+            self.position = 0;
+            self.squeezing = true;
+        }
+        let element = self.state[1 + self.position];
+        self.position += 1;
+        element
+    }
+
+    fn rate(&self) -> usize {
+        self.params.width - 1
+    }
+}
+
+/// fixed length hash, the input length is bound in the capacity element
+pub fn hash(params: &PoseidonParams, inputs: &[Base]) -> Base {
+    let mut sponge = Sponge::new(params, Base::from(inputs.len() as u64));
+    inputs.iter().for_each(|input| sponge.absorb(*input));
+    sponge.squeeze()
+}
+
+// self-shrinking Grain LFSR of the Poseidon reference parameter script
+struct Grain {
+    bits: [bool; 80],
+    head: usize,
+}
+
+impl Grain {
+    fn new(sbox: u16, width: u16, full_rounds: u16, partial_rounds: u16) -> Self {
+        let mut bits = [true; 80];
+        let fields = [
+            (PRIME_FIELD, 2),
+            (sbox, 4),
+            (FIELD_SIZE, 12),
+            (width, 12),
+            (full_rounds, 10),
+            (partial_rounds, 10),
+        ];
+        let mut index = 0;
+        for (value, length) in fields {
+            for i in (0..length).rev() {
+                bits[index] = (value >> i) & 1 == 1;
+                index += 1;
+            }
+        }
+
+        let mut grain = Self { bits, head: 0 };
+        for _ in 0..160 {
+            grain.step();
+        }
+        grain
+    }
+
+    fn step(&mut self) -> bool {
+        let bit = |i: usize| self.bits[(self.head + i) % 80];
+        let new_bit = bit(62) ^ bit(51) ^ bit(38) ^ bit(23) ^ bit(13) ^ bit(0);
+        self.bits[self.head] = new_bit;
+        self.head = (self.head + 1) % 80;
+        new_bit
+    }
+
+    fn next_bit(&mut self) -> bool {
+        while !self.step() {
+            self.step();
+        }
+        self.step()
+    }
+
+    // big endian bits as little endian bytes
+    fn next_bytes(&mut self) -> [u8; 32] {
+        let mut limbs = [0u64; 4];
+        for _ in 0..FIELD_SIZE {
+            let carry = limbs
+                .iter_mut()
+                .fold(self.next_bit() as u64, |carry, limb| {
+                    let next = *limb >> 63;
+                    *limb = (*limb << 1) | carry;
+                    next
+                });
+            debug_assert_eq!(carry, 0);
+        }
+
+        let mut bytes = [0; 32];
+        for (chunk, limb) in bytes.chunks_mut(8).zip(limbs) {
+            chunk.copy_from_slice(&limb.to_le_bytes());
+        }
+        bytes
+    }
+
+    // samples out of range are rejected
+    fn field_element(&mut self) -> Base {
+        loop {
+            if let Some(element) = Base::from_bytes(self.next_bytes()) {
+                return element;
+            }
+        }
+    }
+
+    // samples are reduced, M_ij = 1 / (x_i + y_j)
+    fn cauchy_matrix(&mut self, width: usize) -> Vec<Vec<Base>> {
+        loop {
+            let samples: Vec<Base> = (0..2 * width)
+                .map(|_| {
+                    let mut wide = [0; 64];
+                    wide[..32].copy_from_slice(&self.next_bytes());
+                    Base::from_bytes_wide(&wide)
+                })
+                .collect();
+            let (xs, ys) = samples.split_at(width);
+
+            let distinct = samples
+                .iter()
+                .enumerate()
+                .all(|(i, a)| samples[i + 1..].iter().all(|b| a != b));
+            let matrix: Option<Vec<Vec<Base>>> = xs
+                .iter()
+                .map(|x| ys.iter().map(|y| (*x + *y).invert()).collect())
+                .collect();
+
+            match matrix {
+                Some(matrix) if distinct => return matrix,
+                _ => continue,
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use proptest::{collection::vec, prelude::*};
+
+    prop_compose! {
+        fn arb_field()(
+            bytes in vec(any::<u8>(), 64)
+        ) -> Base {
+            Base::from_bytes_wide(&<[u8; 64]>::try_from(bytes).unwrap())
+        }
+    }
+
+    fn from_hex(hex: &str) -> Base {
+        let mut bytes = [0; 32];
+        for (i, byte) in bytes.iter_mut().rev().enumerate() {
+            *byte = u8::from_str_radix(&hex[2 * i..2 * i + 2], 16).unwrap();
+        }
+        Base::from_bytes(bytes).unwrap()
+    }
+
+    #[test]
+    fn test_round_constants() {
+        // generate_parameters_grain.sage 1 1 255 9 8 57 0x73eda753...00000001
+        let mut grain = Grain::new(1, 9, 8, 57);
+        let expected = [
+            "6ce90d12c4045fe08c3caddb776dd84ac52b4ae7e48cd49443984154f2f4c2f9",
+            "0ccd9b480c84022b25a75b52c8e89d80b4fa6378d32e58f3a175504940bcec39",
+            "513ad11016ab7bb8c7ce9ae51256040e062487edf7168d206ba1b86dfe963629",
+            "11f7cb649a0e7509a23d14173a5de7151173d08eacba753b99826b8b3779200a",
+        ];
+
+        for hex in expected {
+            assert_eq!(grain.field_element(), from_hex(hex))
+        }
+    }
+
+    #[test]
+    fn test_permutation() {
+        let width3 = PoseidonParams::width3();
+        let mut state = [Base::from(0), Base::from(1), Base::from(2)];
+        width3.permute(&mut state);
+
+        // poseidonperm_x5_255_3 reference test vector
+        assert_eq!(
+            state,
+            [
+                from_hex("28ce19420fc246a05553ad1e8c98f5c9d67166be2c18e9e4cb4b4e317dd2a78a"),
+                from_hex("51f3e312c95343a896cfd8945ea82ba956c1118ce9b9859b6ea56637b4b1ddc4"),
+                from_hex("3b2b69139b235626a0bfb56c9527ae66a7bf486ad8c11c14d1da0c69bbe0f79a"),
+            ]
+        );
+
+        let width5 = PoseidonParams::width5();
+        let mut state = [0, 1, 2, 3, 4].map(Base::from);
+        width5.permute(&mut state);
+
+        // poseidonperm_x5_255_5 reference test vector
+        assert_eq!(
+            state,
+            [
+                from_hex("2a918b9c9f9bd7bb509331c81e297b5707f6fc7393dcee1b13901a0b22202e18"),
+                from_hex("65ebf8671739eeb11fb217f2d5c5bf4a0c3f210e3f3cd3b08b5db75675d797f7"),
+                from_hex("2cc176fc26bc70737a696a9dfd1b636ce360ee76926d182390cdb7459cf585ce"),
+                from_hex("4dc4e29d283afd2a491fe6aef122b9a968e74eff05341f3cc23fda1781dcb566"),
+                from_hex("03ff622da276830b9451b88b85e6184fd6ae15c8ab3ee25a5667be8592cce3b1"),
+            ]
+        );
+    }
+
+    proptest! {
+        #![proptest_config(ProptestConfig::with_cases(20))]
+        #[test]
+        fn test_sponge(a in arb_field(), b in arb_field(), c in arb_field()) {
+            let params = PoseidonParams::width3();
+
+            let mut sponge = Sponge::new(params, Base::from(3));
+            sponge.absorb(a);
+            sponge.absorb(b);
+            sponge.absorb(c);
+
+            assert_eq!(sponge.squeeze(), hash(params, &[a, b, c]));
+            assert_ne!(hash(params, &[a, b, c]), hash(params, &[b, a, c]));
+            assert_ne!(hash(params, &[a, b]), hash(params, &[a, b, Base::zero()]))
+        }
+    }
+}
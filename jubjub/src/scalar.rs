@@ -1,9 +1,11 @@
 //! Jubjub scalar field
 use core::fmt::{Debug, Formatter, Result};
-use core::ops::{Add, Mul, Sub};
+use core::ops::{Add, Mul, Neg, Sub};
 use rand_core::RngCore;
 
-use crate::limbs::{add, double, from_u512, mont, mul, square, sub, to_nafs, Nafs};
+use crate::limbs::{
+    add, double, from_u512, invert, little_fermat, mont, mul, neg, square, sub, to_nafs, Nafs,
+};
 use crate::math::sbb;
 
 const MODULUS: [u64; 4] = [
@@ -44,20 +46,28 @@ const INV: u64 = 0x1ba3a358ef788ef9;
 pub struct Scalar(pub [u64; 4]);
 
 impl Scalar {
-    pub(crate) fn one() -> Self {
+    pub fn zero() -> Self {
+        Self([0; 4])
+    }
+
+    pub fn one() -> Self {
         Self(R)
     }
 
-    pub(crate) fn double(self) -> Self {
+    pub fn double(self) -> Self {
         Self(double(self.0, MODULUS))
     }
 
-    pub(crate) fn square(self) -> Self {
+    pub fn square(self) -> Self {
         Self(square(self.0, MODULUS, INV))
     }
 
+    pub fn invert(self) -> Option<Self> {
+        invert(self.0, little_fermat(MODULUS), R, MODULUS, INV).map(Self)
+    }
+
     // map raw limbs to montgomery form
-    pub(crate) const fn to_mont(raw: [u64; 4]) -> Self {
+    pub const fn to_mont(raw: [u64; 4]) -> Self {
         Self(mul(raw, R2, MODULUS, INV))
     }
 
@@ -143,6 +153,12 @@ impl Scalar {
     }
 }
 
+impl From<u64> for Scalar {
+    fn from(value: u64) -> Self {
+        Self::to_mont([value, 0, 0, 0])
+    }
+}
+
 impl Add for Scalar {
     type Output = Self;
 
@@ -151,6 +167,14 @@ impl Add for Scalar {
     }
 }
 
+impl Neg for Scalar {
+    type Output = Self;
+
+    fn neg(self) -> Self {
+        Self(neg(self.0, MODULUS))
+    }
+}
+
 impl Sub for Scalar {
     type Output = Self;
 
@@ -224,4 +248,14 @@ mod tests {
             assert_eq!(additive, doubling)
         }
     }
+    proptest! {
+        #![proptest_config(ProptestConfig::with_cases(100))]
+        #[test]
+        fn test_invert(a in arb_field()) {
+            match a.invert() {
+                Some(inv) => assert_eq!(a * inv, Scalar::one()),
+                None => assert_eq!(a, Scalar::zero()),
+            }
+        }
+    }
 }
@@ -0,0 +1,36 @@
+use crate::signature::Signature;
+
+use jubjub::affine::Affine;
+use jubjub::scalar::Scalar;
+
+/// aggregated pre-signature `(R, s')`, completed signature is `(R + T, s' + t)`
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct PreSignature {
+    r: Affine,
+    s: Scalar,
+}
+
+impl PreSignature {
+    pub fn new(r: Affine, s: Scalar) -> Self {
+        Self { r, s }
+    }
+
+    pub fn get_r(&self) -> Affine {
+        self.r
+    }
+
+    pub fn get_s(&self) -> Scalar {
+        self.s
+    }
+
+    pub fn adapt(&self, t: Scalar) -> Signature {
+        let r = self.r + Affine::basepoint() * t;
+
+        Signature::new(r.to_affine(), self.s + t)
+    }
+
+    /// `t = s - s'`
+    pub fn extract(&self, sig: &Signature) -> Scalar {
+        sig.get_s() - self.s
+    }
+}
@@ -1,60 +1,133 @@
+use crate::adaptor::PreSignature;
 use crate::signature::Signature;
 
+use core::marker::PhantomData;
 use jubjub::affine::Affine;
 use jubjub::scalar::Scalar;
-use schnorr::hash::SchnorrHash;
+use schnorr::hash::{tag, ChallengeHash, TaggedHash};
 use schnorr::private::PrivateKey;
 use schnorr::public::PublicKey;
+use schnorr::transcript::Transcript;
 
-pub(crate) struct PublicParams {
+pub struct PublicParams<H: ChallengeHash = TaggedHash> {
     // R
     randomness: Affine,
     // X
     public_key: PublicKey,
+    // T
+    adaptor: Affine,
     // c
     challenge: Scalar,
+    hash: PhantomData<H>,
 }
 
 impl PublicParams {
-    fn new(m: &[u8], a: PublicKey, b: PublicKey, a_r: Affine, b_r: Affine) -> Self {
+    pub fn new(m: &[u8], a: PublicKey, b: PublicKey, a_r: Affine, b_r: Affine) -> Self {
+        Self::with_hash(m, a, b, a_r, b_r)
+    }
+
+    pub fn with_adaptor(
+        m: &[u8],
+        a: PublicKey,
+        b: PublicKey,
+        a_r: Affine,
+        b_r: Affine,
+        adaptor: Affine,
+    ) -> Self {
+        Self::with_hash_and_adaptor(m, a, b, a_r, b_r, adaptor)
+    }
+}
+
+impl<H: ChallengeHash> PublicParams<H> {
+    pub fn with_hash(m: &[u8], a: PublicKey, b: PublicKey, a_r: Affine, b_r: Affine) -> Self {
+        Self::with_hash_and_adaptor(m, a, b, a_r, b_r, Affine::identity())
+    }
+
+    /// challenge over `R + T`, partial signatures sum to a pre-signature
+    pub fn with_hash_and_adaptor(
+        m: &[u8],
+        a: PublicKey,
+        b: PublicKey,
+        a_r: Affine,
+        b_r: Affine,
+        adaptor: Affine,
+    ) -> Self {
         let randomness = (a_r + b_r).to_affine();
-        let a_1 = SchnorrHash::aggregate(&a.to_bytes(), &b.to_bytes(), &a.to_bytes());
-        let a_2 = SchnorrHash::aggregate(&a.to_bytes(), &b.to_bytes(), &b.to_bytes());
+        let a_1 = coefficient(a, b, a);
+        let a_2 = coefficient(a, b, b);
         let aggregated_point = a * a_1 + b * a_2;
         let public_key = PublicKey::new(aggregated_point.to_affine());
-        let challenge = SchnorrHash::aggregate(&randomness.to_bytes(), &public_key.to_bytes(), m);
+        let challenge = challenge::<H>((randomness + adaptor).to_affine(), public_key, m);
 
         Self {
             randomness,
             public_key,
+            adaptor,
             challenge,
+            hash: PhantomData,
         }
     }
 
-    fn cosign(&self, a: Scalar, r: Scalar, private_key: PrivateKey) -> Scalar {
+    pub fn cosign(&self, a: Scalar, r: Scalar, private_key: PrivateKey) -> Scalar {
         r + private_key * self.challenge * a
     }
 
-    fn generate_signature(&self, s_1: Scalar, s_2: Scalar) -> Signature {
+    pub fn generate_signature(&self, s_1: Scalar, s_2: Scalar) -> Signature {
         let s = s_1 + s_2;
 
         Signature::new(self.randomness, s)
     }
 
-    fn verify(&self, m: &[u8], sig: Signature) -> bool {
+    pub fn generate_pre_signature(&self, s_1: Scalar, s_2: Scalar) -> PreSignature {
+        PreSignature::new(self.randomness, s_1 + s_2)
+    }
+
+    /// `s' * G == R + c * X` for the challenge bound to `R + T`
+    pub fn verify_pre_signature(&self, pre: &PreSignature) -> bool {
+        let rc = self.public_key * self.challenge + pre.get_r();
+        let gs = Affine::basepoint() * pre.get_s();
+
+        rc.to_affine() == gs.to_affine()
+    }
+
+    pub fn adaptor(&self) -> Affine {
+        self.adaptor
+    }
+
+    pub fn verify(&self, m: &[u8], sig: Signature) -> bool {
         let r = sig.get_r();
         let s = sig.get_s();
-        let rc = r + self.public_key * self.challenge;
+        let challenge = challenge::<H>(r, self.public_key, m);
+        let rc = r + self.public_key * challenge;
         let gs = Affine::basepoint() * s;
 
         rc.to_affine() == gs.to_affine()
     }
 }
 
+// a_i = H_agg(L, X_i)
+pub fn coefficient(a: PublicKey, b: PublicKey, x: PublicKey) -> Scalar {
+    let mut transcript = Transcript::new(tag::AGG);
+    transcript.append_message(b"L", &a.to_bytes());
+    transcript.append_message(b"L", &b.to_bytes());
+    transcript.append_message(b"X", &x.to_bytes());
+    transcript.challenge_scalar(b"a")
+}
+
+// c = H_sig(R, X, m)
+pub(crate) fn challenge<H: ChallengeHash>(r: Affine, x: PublicKey, m: &[u8]) -> Scalar {
+    let mut transcript = Transcript::<H>::with_hash(tag::SIG);
+    transcript.append_point(b"R", &r);
+    transcript.append_message(b"X", &x.to_bytes());
+    transcript.append_message(b"m", m);
+    transcript.challenge_scalar(b"c")
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
     use proptest::{collection::vec, prelude::*};
+    use schnorr::hash::Sha512Hash;
 
     prop_compose! {
         fn arb_field()(
@@ -76,8 +149,8 @@ mod tests {
             let bob_public_key = bob_private_key.to_public_key();
             let bob_public_r = Affine::basepoint() * r2;
 
-            let a1 = SchnorrHash::aggregate(&alice_public_key.to_bytes(), &bob_public_key.to_bytes(), &alice_public_key.to_bytes());
-            let a2 = SchnorrHash::aggregate(&alice_public_key.to_bytes(), &bob_public_key.to_bytes(), &bob_public_key.to_bytes());
+            let a1 = coefficient(alice_public_key, bob_public_key, alice_public_key);
+            let a2 = coefficient(alice_public_key, bob_public_key, bob_public_key);
 
             let public_params = PublicParams::new(message, alice_public_key, bob_public_key, alice_public_r.to_affine(), bob_public_r.to_affine());
 
@@ -88,4 +161,58 @@ mod tests {
             assert!(public_params.verify(message, signature))
         }
     }
+
+    proptest! {
+        #![proptest_config(ProptestConfig::with_cases(20))]
+        #[test]
+        fn test_signature_aggregation_with_hash(alice in arb_field(), bob in arb_field(), r1 in arb_field(), r2 in arb_field()) {
+            let message = b"test";
+            let alice_private_key = PrivateKey::new(alice);
+            let alice_public_key = alice_private_key.to_public_key();
+            let alice_public_r = Affine::basepoint() * r1;
+            let bob_private_key = PrivateKey::new(bob);
+            let bob_public_key = bob_private_key.to_public_key();
+            let bob_public_r = Affine::basepoint() * r2;
+
+            let a1 = coefficient(alice_public_key, bob_public_key, alice_public_key);
+            let a2 = coefficient(alice_public_key, bob_public_key, bob_public_key);
+
+            let public_params = PublicParams::<Sha512Hash>::with_hash(message, alice_public_key, bob_public_key, alice_public_r.to_affine(), bob_public_r.to_affine());
+
+            let s_1 = public_params.cosign(a1, r1, alice_private_key);
+            let s_2 = public_params.cosign(a2, r2, bob_private_key);
+            let signature = public_params.generate_signature(s_1, s_2);
+
+            assert!(public_params.verify(message, signature))
+        }
+    }
+
+    proptest! {
+        #![proptest_config(ProptestConfig::with_cases(20))]
+        #[test]
+        fn test_adaptor_aggregation(alice in arb_field(), bob in arb_field(), r1 in arb_field(), r2 in arb_field(), t in arb_field()) {
+            let message = b"swap";
+            let alice_private_key = PrivateKey::new(alice);
+            let alice_public_key = alice_private_key.to_public_key();
+            let alice_public_r = Affine::basepoint() * r1;
+            let bob_private_key = PrivateKey::new(bob);
+            let bob_public_key = bob_private_key.to_public_key();
+            let bob_public_r = Affine::basepoint() * r2;
+            let adaptor = (Affine::basepoint() * t).to_affine();
+
+            let a1 = coefficient(alice_public_key, bob_public_key, alice_public_key);
+            let a2 = coefficient(alice_public_key, bob_public_key, bob_public_key);
+
+            let public_params = PublicParams::with_adaptor(message, alice_public_key, bob_public_key, alice_public_r.to_affine(), bob_public_r.to_affine(), adaptor);
+
+            let s_1 = public_params.cosign(a1, r1, alice_private_key);
+            let s_2 = public_params.cosign(a2, r2, bob_private_key);
+            let pre = public_params.generate_pre_signature(s_1, s_2);
+            assert!(public_params.verify_pre_signature(&pre));
+
+            let signature = pre.adapt(t);
+            assert_eq!(pre.extract(&signature), t);
+            assert!(public_params.verify(message, signature))
+        }
+    }
 }
@@ -0,0 +1,403 @@
+//! Transport agnostic signing session driver
+//!
+//! Runs the commitment, nonce and partial signature rounds over any
+//! `Sink<Message>` broadcasting to the other signers and any
+//! `Stream<Item = (u32, Message)>` receiving from them. The stream pairs each
+//! message with its sender's index as authenticated by the transport, and a
+//! message claiming another signer is dropped, so blame never rests on the
+//! sender's own claim. A round waits at most `timeout`, then re-broadcasts
+//! every message sent so far and waits again up to `retries` times. Nonces
+//! are checked against their commitments and partial signatures against
+//! `s_i * G = R_i + c * a_i * g_acc * X_i`, so a misbehaving signer is named
+//! in the abort reason. Nonce commitments, nonces and partial signatures come
+//! from a [`Signer`], whose calls block the task.
+use crate::key_agg::KeyAggContext;
+use crate::session::{commit_nonce, verify_xonly, Session};
+use crate::signature::Signature;
+use crate::signer::{LocalSigner, Signer, SignerError};
+use crate::wire::{Message, Payload};
+
+use core::fmt::{self, Display, Formatter};
+use core::future::Future;
+use core::time::Duration;
+use futures::future::{select, Either};
+use futures::{Sink, SinkExt, Stream, StreamExt};
+use jubjub::affine::Affine;
+use jubjub::scalar::Scalar;
+
+/// source of the round timeouts, a runtime's sleep or simulated time
+pub trait Timer {
+    type Sleep: Future<Output = ()>;
+
+    fn sleep(&self, duration: Duration) -> Self::Sleep;
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct DriverConfig {
+    pub timeout: Duration,
+    pub retries: u32,
+}
+
+impl Default for DriverConfig {
+    fn default() -> Self {
+        Self {
+            timeout: Duration::from_secs(5),
+            retries: 3,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Round {
+    Commitment,
+    Nonce,
+    PartialSignature,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Abort {
+    /// signers still missing after every retry
+    Timeout {
+        round: Round,
+        missing: Vec<u32>,
+    },
+    /// signer sent two different messages for one round
+    Equivocation {
+        signer: u32,
+    },
+    /// nonce doesn't match the commitment
+    InvalidNonce {
+        signer: u32,
+    },
+    InvalidPartialSignature {
+        signer: u32,
+    },
+    InvalidSignature,
+    /// own signer failed
+    Signer(String),
+    Disconnected,
+    Transport(String),
+}
+
+impl Display for Abort {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match self {
+            Self::Timeout { round, missing } => {
+                write!(f, "{:?} round timed out waiting for {:?}", round, missing)
+            }
+            Self::Equivocation { signer } => write!(f, "signer {} equivocated", signer),
+            Self::InvalidNonce { signer } => {
+                write!(f, "nonce of signer {} doesn't match its commitment", signer)
+            }
+            Self::InvalidPartialSignature { signer } => {
+                write!(f, "partial signature of signer {} is invalid", signer)
+            }
+            Self::InvalidSignature => write!(f, "combined signature is invalid"),
+            Self::Signer(error) => write!(f, "{}", error),
+            Self::Disconnected => write!(f, "transport closed"),
+            Self::Transport(error) => write!(f, "{}", error),
+        }
+    }
+}
+
+impl std::error::Error for Abort {}
+
+impl From<SignerError> for Abort {
+    fn from(error: SignerError) -> Self {
+        Self::Signer(error.to_string())
+    }
+}
+
+pub struct Driver<S: Signer = LocalSigner> {
+    context: KeyAggContext,
+    signer: S,
+    index: u32,
+    session_id: [u8; 32],
+    message: Vec<u8>,
+    config: DriverConfig,
+}
+
+// messages received so far, indexed by signer
+struct Rounds {
+    commitments: Vec<Option<[u8; 32]>>,
+    nonces: Vec<Option<Affine>>,
+    partials: Vec<Option<Scalar>>,
+    signature: Option<Signature>,
+}
+
+impl<S: Signer> Driver<S> {
+    /// fails if the signer's key is not one of the context's signers
+    pub fn new(
+        context: &KeyAggContext,
+        mut signer: S,
+        session_id: [u8; 32],
+        m: &[u8],
+        config: DriverConfig,
+    ) -> Result<Self, SignerError> {
+        let public_key = signer.public_key()?;
+        let index = context
+            .keys()
+            .iter()
+            .position(|key| *key == public_key)
+            .ok_or_else(|| SignerError::Rejected("public key is not one of the signers".into()))?;
+
+        Ok(Self {
+            context: context.clone(),
+            signer,
+            index: index as u32,
+            session_id,
+            message: m.to_vec(),
+            config,
+        })
+    }
+
+    pub fn index(&self) -> u32 {
+        self.index
+    }
+
+    pub async fn run<Si, St, T>(
+        mut self,
+        mut sink: Si,
+        mut stream: St,
+        timer: &T,
+    ) -> Result<Signature, Abort>
+    where
+        Si: Sink<Message> + Unpin,
+        Si::Error: Display,
+        St: Stream<Item = (u32, Message)> + Unpin,
+        T: Timer,
+    {
+        let me = self.index as usize;
+        let mut rounds = Rounds::new(self.context.keys().len());
+        let mut sent = Vec::new();
+
+        let commitment = self.signer.nonce()?;
+        rounds.commitments[me] = Some(commitment);
+        self.broadcast(
+            &mut sink,
+            &mut sent,
+            Payload::NonceCommitment { commitment },
+        )
+        .await?;
+        if let Some(signature) = self
+            .collect(
+                Round::Commitment,
+                &mut rounds,
+                &sent,
+                &mut sink,
+                &mut stream,
+                timer,
+            )
+            .await?
+        {
+            return Ok(signature);
+        }
+
+        let commitments: Vec<[u8; 32]> = rounds.commitments.iter().map(|t| t.unwrap()).collect();
+        let nonce = self
+            .signer
+            .reveal(&self.context, &self.message, &commitments)?;
+        rounds.nonces[me] = Some(nonce);
+        self.broadcast(&mut sink, &mut sent, Payload::NonceReveal { nonce })
+            .await?;
+        if let Some(signature) = self
+            .collect(
+                Round::Nonce,
+                &mut rounds,
+                &sent,
+                &mut sink,
+                &mut stream,
+                timer,
+            )
+            .await?
+        {
+            return Ok(signature);
+        }
+        let nonces: Vec<Affine> = rounds.nonces.iter().map(|nonce| nonce.unwrap()).collect();
+        for (i, nonce) in nonces.iter().enumerate() {
+            if rounds.commitments[i] != Some(commit_nonce(nonce)) {
+                return Err(Abort::InvalidNonce { signer: i as u32 });
+            }
+        }
+
+        let s = self
+            .signer
+            .partial_sign(&self.context, &self.message, &nonces)?;
+        let session = Session::new(&self.context, &self.message, &nonces);
+        rounds.partials[me] = Some(s);
+        self.broadcast(&mut sink, &mut sent, Payload::PartialSignature { s })
+            .await?;
+        if let Some(signature) = self
+            .collect(
+                Round::PartialSignature,
+                &mut rounds,
+                &sent,
+                &mut sink,
+                &mut stream,
+                timer,
+            )
+            .await?
+        {
+            return Ok(signature);
+        }
+        let partials: Vec<Scalar> = rounds.partials.iter().map(|s| s.unwrap()).collect();
+        for (i, (key, s)) in self.context.keys().iter().zip(&partials).enumerate() {
+            let weight = session.key_challenge(key).unwrap();
+            if (Affine::basepoint() * *s).to_affine() != (*key * weight + nonces[i]).to_affine() {
+                return Err(Abort::InvalidPartialSignature { signer: i as u32 });
+            }
+        }
+
+        let signature = session.generate_signature(&partials);
+        if !session.verify(&self.message, signature.clone()) {
+            return Err(Abort::InvalidSignature);
+        }
+        // lets signers still waiting on a lost partial signature finish
+        let payload = Payload::Signature {
+            signature: signature.clone(),
+        };
+        self.broadcast(&mut sink, &mut sent, payload).await?;
+        Ok(signature)
+    }
+
+    async fn broadcast<Si>(
+        &self,
+        sink: &mut Si,
+        sent: &mut Vec<Message>,
+        payload: Payload,
+    ) -> Result<(), Abort>
+    where
+        Si: Sink<Message> + Unpin,
+        Si::Error: Display,
+    {
+        let message = Message::new(self.session_id, self.index, payload);
+        sent.push(message.clone());
+        send(sink, message).await
+    }
+
+    // `Some` if a valid final signature arrives before the round completes
+    async fn collect<Si, St, T>(
+        &self,
+        round: Round,
+        rounds: &mut Rounds,
+        sent: &[Message],
+        sink: &mut Si,
+        stream: &mut St,
+        timer: &T,
+    ) -> Result<Option<Signature>, Abort>
+    where
+        Si: Sink<Message> + Unpin,
+        Si::Error: Display,
+        St: Stream<Item = (u32, Message)> + Unpin,
+        T: Timer,
+    {
+        for attempt in 0..=self.config.retries {
+            if attempt > 0 {
+                for message in sent {
+                    send(sink, message.clone()).await?;
+                }
+            }
+            let mut sleep = Box::pin(timer.sleep(self.config.timeout));
+            loop {
+                if let Some(signature) = rounds.signature.take() {
+                    return Ok(Some(signature));
+                }
+                if rounds.missing(round).is_empty() {
+                    return Ok(None);
+                }
+                match select(stream.next(), sleep.as_mut()).await {
+                    Either::Left((Some((from, message)), _)) => {
+                        self.receive(rounds, from, message)?
+                    }
+                    Either::Left((None, _)) => return Err(Abort::Disconnected),
+                    Either::Right(_) => break,
+                }
+            }
+        }
+
+        Err(Abort::Timeout {
+            round,
+            missing: rounds.missing(round),
+        })
+    }
+
+    // messages of other sessions, unknown signers and spoofed senders are ignored
+    fn receive(&self, rounds: &mut Rounds, from: u32, message: Message) -> Result<(), Abort> {
+        let signer = message.signer;
+        let i = signer as usize;
+        if message.session_id != self.session_id
+            || signer != from
+            || i >= rounds.nonces.len()
+            || signer == self.index
+        {
+            return Ok(());
+        }
+
+        match message.payload {
+            Payload::NonceCommitment { commitment } => {
+                store(&mut rounds.commitments[i], commitment, signer)
+            }
+            Payload::NonceReveal { nonce } => store(&mut rounds.nonces[i], nonce, signer),
+            Payload::PartialSignature { s } => store(&mut rounds.partials[i], s, signer),
+            Payload::Signature { signature } => {
+                if self.verify(&signature) {
+                    rounds.signature = Some(signature);
+                }
+                Ok(())
+            }
+            Payload::KeyAgg(_) => Ok(()),
+        }
+    }
+
+    fn verify(&self, signature: &Signature) -> bool {
+        verify_xonly(
+            &self.context.xonly_aggregated_key(),
+            &self.message,
+            signature.clone(),
+        )
+    }
+}
+
+impl Rounds {
+    fn new(n: usize) -> Self {
+        Self {
+            commitments: vec![None; n],
+            nonces: vec![None; n],
+            partials: vec![None; n],
+            signature: None,
+        }
+    }
+
+    fn missing(&self, round: Round) -> Vec<u32> {
+        let received: Vec<bool> = match round {
+            Round::Commitment => self.commitments.iter().map(Option::is_some).collect(),
+            Round::Nonce => self.nonces.iter().map(Option::is_some).collect(),
+            Round::PartialSignature => self.partials.iter().map(Option::is_some).collect(),
+        };
+        (0..received.len() as u32)
+            .filter(|i| !received[*i as usize])
+            .collect()
+    }
+}
+
+async fn send<Si>(sink: &mut Si, message: Message) -> Result<(), Abort>
+where
+    Si: Sink<Message> + Unpin,
+    Si::Error: Display,
+{
+    sink.send(message)
+        .await
+        .map_err(|error| Abort::Transport(error.to_string()))
+}
+
+// retransmissions are identical, anything else is equivocation
+fn store<T: PartialEq>(slot: &mut Option<T>, value: T, signer: u32) -> Result<(), Abort> {
+    match slot {
+        Some(stored) if *stored != value => Err(Abort::Equivocation { signer }),
+        Some(_) => Ok(()),
+        None => {
+            *slot = Some(value);
+            Ok(())
+        }
+    }
+}
@@ -0,0 +1,234 @@
+//! Key aggregation context with plain and x-only tweaks
+//!
+//! Follows BIP-327: the aggregate `Q = g_acc * sum(a_i * X_i) + t_acc * G`
+//! tracks the accumulated sign `g_acc` and tweak `t_acc`, x-only keys are the
+//! `y` coordinate alone and lift to the point with even `x`.
+use jubjub::affine::Affine;
+use jubjub::extend::Extended;
+use jubjub::scalar::Scalar;
+use schnorr::hash::tag;
+use schnorr::public::PublicKey;
+use schnorr::transcript::Transcript;
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct KeyAggContext {
+    keys: Vec<PublicKey>,
+    coefficients: Vec<Scalar>,
+    // Q
+    aggregate: Affine,
+    gacc: Scalar,
+    tacc: Scalar,
+}
+
+impl KeyAggContext {
+    /// `None` for an empty key list or an aggregate at infinity
+    pub fn new(keys: &[PublicKey]) -> Option<Self> {
+        let coefficients: Vec<Scalar> = keys.iter().map(|x| coefficient(keys, x)).collect();
+        let aggregate = keys
+            .iter()
+            .zip(&coefficients)
+            .fold(Extended::identity(), |acc, (x, a)| acc + *x * *a);
+        if keys.is_empty() || aggregate.is_identity() {
+            return None;
+        }
+
+        Some(Self {
+            keys: keys.to_vec(),
+            coefficients,
+            aggregate: aggregate.to_affine(),
+            gacc: Scalar::one(),
+            tacc: Scalar::zero(),
+        })
+    }
+
+    /// rebuilds a tweaked context as `Q = g_acc * sum(a_i * X_i) + t_acc * G`,
+    /// `None` unless `g_acc` is `1` or `-1`
+    pub fn from_parts(keys: &[PublicKey], gacc: Scalar, tacc: Scalar) -> Option<Self> {
+        let context = Self::new(keys)?;
+        if gacc != Scalar::one() && gacc != -Scalar::one() {
+            return None;
+        }
+        let aggregate = context.aggregate * gacc + Affine::basepoint() * tacc;
+        if aggregate.is_identity() {
+            return None;
+        }
+
+        Some(Self {
+            aggregate: aggregate.to_affine(),
+            gacc,
+            tacc,
+            ..context
+        })
+    }
+
+    pub fn keys(&self) -> &[PublicKey] {
+        &self.keys
+    }
+
+    /// `a_i` of a participant, `None` if the key isn't part of the context
+    pub fn coefficient(&self, key: &PublicKey) -> Option<Scalar> {
+        let index = self.keys.iter().position(|x| x == key)?;
+        Some(self.coefficients[index])
+    }
+
+    pub fn aggregated_key(&self) -> PublicKey {
+        PublicKey::new(self.aggregate)
+    }
+
+    /// `y` coordinate of `Q` with the sign bit cleared
+    pub fn xonly_aggregated_key(&self) -> [u8; 32] {
+        let mut bytes = self.aggregate.to_bytes();
+        bytes[31] &= 0b01111111;
+        bytes
+    }
+
+    pub fn has_even_x(&self) -> bool {
+        self.aggregate.to_bytes()[31] >> 7 == 0
+    }
+
+    /// final `g`, `-1` if `Q` has odd `x` else `1`
+    pub fn parity(&self) -> Scalar {
+        if self.has_even_x() {
+            Scalar::one()
+        } else {
+            -Scalar::one()
+        }
+    }
+
+    /// `g * Q`, the x-only key lifted to even `x` that signatures verify under
+    pub fn xonly_public_key(&self) -> PublicKey {
+        PublicKey::new((self.aggregate * self.parity()).to_affine())
+    }
+
+    pub fn gacc(&self) -> Scalar {
+        self.gacc
+    }
+
+    pub fn tacc(&self) -> Scalar {
+        self.tacc
+    }
+
+    /// `Q' = Q + t * G`
+    pub fn apply_plain_tweak(&self, tweak: Scalar) -> Option<Self> {
+        self.apply_tweak(tweak, false)
+    }
+
+    /// `Q' = g * Q + t * G` where `g` negates `Q` to even `x` first
+    pub fn apply_xonly_tweak(&self, tweak: Scalar) -> Option<Self> {
+        self.apply_tweak(tweak, true)
+    }
+
+    fn apply_tweak(&self, tweak: Scalar, is_xonly: bool) -> Option<Self> {
+        let g = if is_xonly {
+            self.parity()
+        } else {
+            Scalar::one()
+        };
+        let aggregate = self.aggregate * g + Affine::basepoint() * tweak;
+        if aggregate.is_identity() {
+            return None;
+        }
+
+        Some(Self {
+            keys: self.keys.clone(),
+            coefficients: self.coefficients.clone(),
+            aggregate: aggregate.to_affine(),
+            gacc: g * self.gacc,
+            tacc: tweak + g * self.tacc,
+        })
+    }
+}
+
+// a_i = H_agg(L, X_i)
+fn coefficient(keys: &[PublicKey], x: &PublicKey) -> Scalar {
+    let mut transcript = Transcript::new(tag::AGG);
+    transcript.append_u64(b"n", keys.len() as u64);
+    for key in keys {
+        transcript.append_message(b"L", &key.to_bytes());
+    }
+    transcript.append_message(b"X", &x.to_bytes());
+    transcript.challenge_scalar(b"a")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::session::{verify_xonly, Session};
+    use proptest::{collection::vec, prelude::*};
+    use rand_core::OsRng;
+    use schnorr::private::PrivateKey;
+
+    prop_compose! {
+        fn arb_field()(
+            bytes in vec(any::<u8>(), 64)
+        ) -> Scalar {
+            Scalar::from_bytes_wide(&<[u8; 64]>::try_from(bytes).unwrap())
+        }
+    }
+
+    fn sign_xonly(context: &KeyAggContext, private_keys: &[PrivateKey], m: &[u8]) -> bool {
+        let r: Vec<Scalar> = private_keys.iter().map(|_| Scalar::random(OsRng)).collect();
+        let nonces: Vec<Affine> = r
+            .iter()
+            .map(|r| (Affine::basepoint() * *r).to_affine())
+            .collect();
+        let session = Session::new(context, m, &nonces);
+        let partials: Vec<Scalar> = private_keys
+            .iter()
+            .zip(r)
+            .map(|(key, r)| session.cosign(r, *key).unwrap())
+            .collect();
+        let signature = session.generate_signature(&partials);
+
+        verify_xonly(&context.xonly_aggregated_key(), m, signature)
+    }
+
+    #[test]
+    fn test_final_parity() {
+        let private_keys: Vec<PrivateKey> = (0..3)
+            .map(|_| PrivateKey::new(Scalar::random(OsRng)))
+            .collect();
+        let public_keys: Vec<PublicKey> =
+            private_keys.iter().map(|key| key.to_public_key()).collect();
+        let mut context = KeyAggContext::new(&public_keys).unwrap();
+        let mut parities = [false; 2];
+        let mut i = 0;
+
+        // alternate plain and x-only tweaks until the final Q took both parities
+        while !(parities[0] && parities[1]) {
+            context = if i % 2 == 0 {
+                context.apply_plain_tweak(Scalar::random(OsRng)).unwrap()
+            } else {
+                context.apply_xonly_tweak(Scalar::random(OsRng)).unwrap()
+            };
+            parities[context.has_even_x() as usize] = true;
+            assert!(sign_xonly(&context, &private_keys, b"test"));
+            assert_eq!(
+                context.xonly_public_key().to_bytes(),
+                context.xonly_aggregated_key()
+            );
+            i += 1;
+        }
+    }
+
+    proptest! {
+        #![proptest_config(ProptestConfig::with_cases(10))]
+        #[test]
+        fn test_tweak_chain(keys in vec(arb_field(), 1..4), tweaks in vec((arb_field(), any::<bool>()), 0..4)) {
+            let message = b"test";
+            let private_keys: Vec<PrivateKey> = keys.into_iter().map(PrivateKey::new).collect();
+            let public_keys: Vec<PublicKey> = private_keys.iter().map(|key| key.to_public_key()).collect();
+            let mut context = KeyAggContext::new(&public_keys).unwrap();
+            assert!(sign_xonly(&context, &private_keys, message));
+
+            for (tweak, is_xonly) in tweaks {
+                context = if is_xonly {
+                    context.apply_xonly_tweak(tweak).unwrap()
+                } else {
+                    context.apply_plain_tweak(tweak).unwrap()
+                };
+                assert!(sign_xonly(&context, &private_keys, message));
+            }
+        }
+    }
+}
@@ -1,2 +1,11 @@
-mod aggregation;
-mod signature;
+pub mod adaptor;
+pub mod aggregation;
+pub mod driver;
+pub mod key_agg;
+pub mod nested;
+pub mod session;
+pub mod signature;
+pub mod signer;
+pub mod simple;
+pub mod simulation;
+pub mod wire;
@@ -0,0 +1,203 @@
+//! Nested aggregation, an inner group's aggregate key joins an outer session
+//!
+//! The inner group appears to the outer session as a single participant with
+//! key `Q_in` and nonce `R_in = sum(R_j)`. Each inner signer scales its key
+//! by the outer factor `c * a_in * g_acc`, so the inner partial signatures sum
+//! to the group's outer partial signature. The inner signers run their own
+//! nonce commitment round first: `R_in` is only released once every inner
+//! nonce matches its stored commitment, so a rushing inner signer cannot pick
+//! its nonce after seeing the others.
+use crate::key_agg::KeyAggContext;
+use crate::session::{commit_nonce, Session};
+
+use jubjub::affine::Affine;
+use jubjub::extend::Extended;
+use jubjub::scalar::Scalar;
+use schnorr::hash::ChallengeHash;
+use schnorr::private::PrivateKey;
+use schnorr::public::PublicKey;
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct NestedSigner {
+    inner: KeyAggContext,
+    // t_j of the inner signers, in key order
+    commitments: Option<Vec<[u8; 32]>>,
+}
+
+impl NestedSigner {
+    pub fn new(inner: KeyAggContext) -> Self {
+        Self {
+            inner,
+            commitments: None,
+        }
+    }
+
+    /// `Q_in`, the key registered in the outer context
+    pub fn public_key(&self) -> PublicKey {
+        self.inner.aggregated_key()
+    }
+
+    /// store the inner commitments `t_j`, `false` unless there is one per inner
+    /// key or if other commitments are pending, repeating the pending list is a
+    /// no-op
+    pub fn commit(&mut self, commitments: &[[u8; 32]]) -> bool {
+        if commitments.len() != self.inner.keys().len() {
+            return false;
+        }
+        match &self.commitments {
+            Some(stored) => stored == commitments,
+            None => {
+                self.commitments = Some(commitments.to_vec());
+                true
+            }
+        }
+    }
+
+    /// `R_in = sum(R_j)`, the nonce contributed to the outer session, `None`
+    /// before `commit` or if any `R_j` does not open its commitment
+    pub fn aggregate_nonces(&self, nonces: &[Affine]) -> Option<Affine> {
+        let commitments = self.commitments.as_ref()?;
+        if nonces.len() != commitments.len()
+            || nonces
+                .iter()
+                .zip(commitments)
+                .any(|(nonce, commitment)| commit_nonce(nonce) != *commitment)
+        {
+            return None;
+        }
+        Some(
+            nonces
+                .iter()
+                .fold(Extended::identity(), |acc, r| acc + *r)
+                .to_affine(),
+        )
+    }
+
+    /// `s_j = r_j + c * a_in * g * g_acc * a_j * g_in * x_j`
+    pub fn cosign<H: ChallengeHash>(
+        &self,
+        outer: &Session<H>,
+        r: Scalar,
+        private_key: PrivateKey,
+    ) -> Option<Scalar> {
+        let weight = outer.key_challenge(&self.public_key())?;
+        let a = self.inner.coefficient(&private_key.to_public_key())?;
+        Some(r + private_key * (weight * a * self.inner.gacc()))
+    }
+
+    /// outer partial signature `sum(s_j) + c * a_in * g * g_acc * t_in`,
+    /// consumes the pending commitments
+    pub fn combine<H: ChallengeHash>(
+        &mut self,
+        outer: &Session<H>,
+        partials: &[Scalar],
+    ) -> Option<Scalar> {
+        let weight = outer.key_challenge(&self.public_key())?;
+        self.commitments = None;
+        Some(
+            partials
+                .iter()
+                .fold(weight * self.inner.tacc(), |acc, s| acc + *s),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use proptest::{collection::vec, prelude::*};
+    use rand_core::OsRng;
+
+    prop_compose! {
+        fn arb_field()(
+            bytes in vec(any::<u8>(), 64)
+        ) -> Scalar {
+            Scalar::from_bytes_wide(&<[u8; 64]>::try_from(bytes).unwrap())
+        }
+    }
+
+    proptest! {
+        #![proptest_config(ProptestConfig::with_cases(10))]
+        #[test]
+        fn test_nested_aggregation(alice in arb_field(), bob in arb_field(), carol in arb_field(), tweak in arb_field()) {
+            let message = b"test";
+            let inner_keys = [PrivateKey::new(alice), PrivateKey::new(bob)];
+            let carol = PrivateKey::new(carol);
+            let inner = KeyAggContext::new(&inner_keys.map(|key| key.to_public_key()))
+                .and_then(|context| context.apply_xonly_tweak(tweak))
+                .unwrap();
+            let mut group = NestedSigner::new(inner);
+            let outer = KeyAggContext::new(&[group.public_key(), carol.to_public_key()]).unwrap();
+
+            let inner_r = [Scalar::random(OsRng), Scalar::random(OsRng)];
+            let carol_r = Scalar::random(OsRng);
+            let inner_nonces = inner_r.map(|r| (Affine::basepoint() * r).to_affine());
+            assert!(group.aggregate_nonces(&inner_nonces).is_none());
+            assert!(!group.commit(&[commit_nonce(&inner_nonces[0])]));
+            assert!(group.commit(&inner_nonces.map(|nonce| commit_nonce(&nonce))));
+            let group_nonce = group.aggregate_nonces(&inner_nonces).unwrap();
+            let nonces = [group_nonce, (Affine::basepoint() * carol_r).to_affine()];
+            let session = Session::new(&outer, message, &nonces);
+
+            let inner_partials: Vec<Scalar> = inner_keys
+                .iter()
+                .zip(inner_r)
+                .map(|(key, r)| group.cosign(&session, r, *key).unwrap())
+                .collect();
+            let partials = [
+                group.combine(&session, &inner_partials).unwrap(),
+                session.cosign(carol_r, carol).unwrap(),
+            ];
+
+            assert!(session.verify(message, session.generate_signature(&partials)));
+            assert!(group.cosign(&session, carol_r, carol).is_none())
+        }
+
+        #[test]
+        fn test_nested_commitment_mismatch(alice in arb_field(), bob in arb_field()) {
+            let inner_keys = [PrivateKey::new(alice), PrivateKey::new(bob)];
+            let inner = KeyAggContext::new(&inner_keys.map(|key| key.to_public_key())).unwrap();
+            let mut group = NestedSigner::new(inner);
+
+            let nonces = [
+                (Affine::basepoint() * Scalar::random(OsRng)).to_affine(),
+                (Affine::basepoint() * Scalar::random(OsRng)).to_affine(),
+            ];
+            assert!(group.commit(&nonces.map(|nonce| commit_nonce(&nonce))));
+
+            // bob swaps his nonce after seeing alice's
+            let rushed = [nonces[0], (Affine::basepoint() * Scalar::random(OsRng)).to_affine()];
+            assert!(group.aggregate_nonces(&rushed).is_none());
+            assert!(group.aggregate_nonces(&[nonces[1], nonces[0]]).is_none());
+            assert!(group.aggregate_nonces(&nonces[..1]).is_none());
+            assert!(group.aggregate_nonces(&nonces).is_some())
+        }
+
+        #[test]
+        fn test_nested_commit_pending(alice in arb_field(), bob in arb_field()) {
+            let message = b"test";
+            let inner_keys = [PrivateKey::new(alice), PrivateKey::new(bob)];
+            let inner = KeyAggContext::new(&inner_keys.map(|key| key.to_public_key())).unwrap();
+            let mut group = NestedSigner::new(inner);
+            let outer = KeyAggContext::new(&[group.public_key()]).unwrap();
+
+            let nonces = [
+                (Affine::basepoint() * Scalar::random(OsRng)).to_affine(),
+                (Affine::basepoint() * Scalar::random(OsRng)).to_affine(),
+            ];
+            let commitments = nonces.map(|nonce| commit_nonce(&nonce));
+            assert!(group.commit(&commitments));
+            assert!(group.commit(&commitments));
+
+            // a late commitment list can't replace the pending one
+            let swapped = [commitments[0], commit_nonce(&Affine::basepoint())];
+            assert!(!group.commit(&swapped));
+            assert!(group.aggregate_nonces(&nonces).is_some());
+
+            let session = Session::new(&outer, message, &[group.aggregate_nonces(&nonces).unwrap()]);
+            assert!(group.combine(&session, &[]).is_some());
+            assert!(group.aggregate_nonces(&nonces).is_none());
+            assert!(group.commit(&swapped))
+        }
+    }
+}
@@ -0,0 +1,155 @@
+//! n-party signing session over a key aggregation context
+use crate::aggregation::challenge;
+use crate::key_agg::KeyAggContext;
+use crate::signature::Signature;
+
+use core::marker::PhantomData;
+use jubjub::affine::Affine;
+use jubjub::extend::Extended;
+use jubjub::scalar::Scalar;
+use schnorr::hash::{tag, ChallengeHash, TaggedHash};
+use schnorr::private::PrivateKey;
+use schnorr::public::PublicKey;
+
+pub struct Session<H: ChallengeHash = TaggedHash> {
+    context: KeyAggContext,
+    // R
+    randomness: Affine,
+    // c
+    challenge: Scalar,
+    hash: PhantomData<H>,
+}
+
+impl Session {
+    pub fn new(context: &KeyAggContext, m: &[u8], nonces: &[Affine]) -> Self {
+        Self::with_hash(context, m, nonces)
+    }
+}
+
+impl<H: ChallengeHash> Session<H> {
+    pub fn with_hash(context: &KeyAggContext, m: &[u8], nonces: &[Affine]) -> Self {
+        let randomness = nonces
+            .iter()
+            .fold(Extended::identity(), |acc, r| acc + *r)
+            .to_affine();
+        let challenge = challenge::<H>(randomness, context.xonly_public_key(), m);
+
+        Self {
+            context: context.clone(),
+            randomness,
+            challenge,
+            hash: PhantomData,
+        }
+    }
+
+    /// `s_i = r_i + c * a_i * g * g_acc * x_i`, `None` for a key outside the context
+    pub fn cosign(&self, r: Scalar, private_key: PrivateKey) -> Option<Scalar> {
+        let weight = self.key_challenge(&private_key.to_public_key())?;
+        Some(r + private_key * weight)
+    }
+
+    /// `c * a_i * g * g_acc`, the factor of participant `X_i`'s private key
+    pub fn key_challenge(&self, key: &PublicKey) -> Option<Scalar> {
+        let a = self.context.coefficient(key)?;
+        Some(self.challenge * a * self.context.parity() * self.context.gacc())
+    }
+
+    /// `s = sum(s_i) + c * g * t_acc`
+    pub fn generate_signature(&self, partials: &[Scalar]) -> Signature {
+        let tweak = self.challenge * self.context.parity() * self.context.tacc();
+        let s = partials.iter().fold(tweak, |acc, s| acc + *s);
+
+        Signature::new(self.randomness, s)
+    }
+
+    pub fn context(&self) -> &KeyAggContext {
+        &self.context
+    }
+
+    pub fn verify(&self, m: &[u8], sig: Signature) -> bool {
+        verify_lifted::<H>(self.context.xonly_public_key(), m, sig)
+    }
+}
+
+/// verifies against the x-only aggregated key, `false` if it isn't a valid `y`
+pub fn verify_xonly(xonly: &[u8; 32], m: &[u8], sig: Signature) -> bool {
+    if xonly[31] >> 7 != 0 {
+        return false;
+    }
+    match PublicKey::from_bytes(*xonly) {
+        Some(public_key) => verify_lifted::<TaggedHash>(public_key, m, sig),
+        None => false,
+    }
+}
+
+// s * G = R + c * P with P the even x lift
+fn verify_lifted<H: ChallengeHash>(public_key: PublicKey, m: &[u8], sig: Signature) -> bool {
+    let r = sig.get_r();
+    let s = sig.get_s();
+    let challenge = challenge::<H>(r, public_key, m);
+    let rc = r + public_key * challenge;
+    let gs = Affine::basepoint() * s;
+
+    rc.to_affine() == gs.to_affine()
+}
+
+/// `t_i = H_com(R_i)`, published before any nonce is revealed
+pub fn commit_nonce(nonce: &Affine) -> [u8; 32] {
+    let digest = TaggedHash::new(tag::COM)
+        .chain(&nonce.to_bytes())
+        .finalize_bytes();
+    digest[..32].try_into().unwrap()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use proptest::{collection::vec, prelude::*};
+    use rand_core::OsRng;
+
+    prop_compose! {
+        fn arb_field()(
+            bytes in vec(any::<u8>(), 64)
+        ) -> Scalar {
+            Scalar::from_bytes_wide(&<[u8; 64]>::try_from(bytes).unwrap())
+        }
+    }
+
+    fn sign_and_verify(context: &KeyAggContext, private_keys: &[PrivateKey], m: &[u8]) -> bool {
+        let r: Vec<Scalar> = private_keys.iter().map(|_| Scalar::random(OsRng)).collect();
+        let nonces: Vec<Affine> = r
+            .iter()
+            .map(|r| (Affine::basepoint() * *r).to_affine())
+            .collect();
+        let session = Session::new(context, m, &nonces);
+        let partials: Vec<Scalar> = private_keys
+            .iter()
+            .zip(r)
+            .map(|(key, r)| session.cosign(r, *key).unwrap())
+            .collect();
+        let signature = session.generate_signature(&partials);
+
+        session.verify(m, signature)
+    }
+
+    proptest! {
+        #![proptest_config(ProptestConfig::with_cases(10))]
+        #[test]
+        fn test_tweaked_session(keys in vec(arb_field(), 1..4), t1 in arb_field(), t2 in arb_field()) {
+            let message = b"test";
+            let private_keys: Vec<PrivateKey> = keys.into_iter().map(PrivateKey::new).collect();
+            let public_keys: Vec<PublicKey> = private_keys.iter().map(|key| key.to_public_key()).collect();
+            let context = KeyAggContext::new(&public_keys).unwrap();
+            assert!(sign_and_verify(&context, &private_keys, message));
+
+            let tweaked = context.apply_xonly_tweak(t1).unwrap().apply_plain_tweak(t2).unwrap();
+            assert!(sign_and_verify(&tweaked, &private_keys, message));
+
+            // x-only tweak lifts Q to even x before adding t * G
+            let lifted = PublicKey::from_bytes(context.xonly_aggregated_key()).unwrap();
+            let expected = (lifted.to_affine() + Affine::basepoint() * t1).to_affine();
+            assert_eq!(context.apply_xonly_tweak(t1).unwrap().aggregated_key().to_affine(), expected);
+            assert!(Session::new(&tweaked, message, &[]).cosign(Scalar::one(), PrivateKey::new(Scalar::one())).is_none())
+        }
+    }
+}
@@ -1,6 +1,7 @@
 use jubjub::affine::Affine;
 use jubjub::scalar::Scalar;
 
+#[derive(Clone, Debug, PartialEq, Eq)]
 pub struct Signature {
     pub(crate) r: [u8; 32],
     pub(crate) s: [u8; 32],
@@ -21,4 +22,19 @@ impl Signature {
     pub fn get_s(&self) -> Scalar {
         Scalar::from_bytes(self.s).unwrap()
     }
+
+    /// `R || s`
+    pub fn to_bytes(&self) -> [u8; 64] {
+        let mut bytes = [0; 64];
+        bytes[..32].copy_from_slice(&self.r);
+        bytes[32..].copy_from_slice(&self.s);
+        bytes
+    }
+
+    /// `None` unless `R` is on the curve and `s` is canonical
+    pub fn from_bytes(bytes: &[u8; 64]) -> Option<Self> {
+        let r = Affine::from_bytes(bytes[..32].try_into().unwrap())?;
+        let s = Scalar::from_bytes(bytes[32..].try_into().unwrap())?;
+        Some(Self::new(r, s))
+    }
 }
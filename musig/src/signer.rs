@@ -0,0 +1,689 @@
+//! Signing keys behind a trait, in memory or in another process
+//!
+//! MuSig nonces are generated and kept by the signer, `partial_sign` gets
+//! the whole session so the signer recomputes the challenge itself and
+//! consumes the nonce, a coordinator never sees `r_i` or `x_i`. The signer
+//! enforces commit-then-reveal: `nonce` hands out only `H_com(R_i)`, `reveal`
+//! binds `R_i` to the full commitment list, the key aggregation context and
+//! the message before releasing it, and `partial_sign` refuses nonces that
+//! don't open those commitments or a different context or message, so the
+//! coordinator can't choose the other nonces after seeing `R_i` nor reuse
+//! `R_i` for a second challenge. At most
+//! `MAX_NONCES` nonces are pending, the oldest is discarded beyond that.
+//!
+//! The remote protocol exchanges frames `len (u32 le) || body`. A request
+//! body is `op || payload`, a response body is `0 || payload` or
+//! `1 || reason`.
+//!
+//! | op | request | response |
+//! |----|---------|----------|
+//! | 0 public key | | `X` |
+//! | 1 sign | `m` | `s \|\| e` |
+//! | 2 nonce | | `t_i` |
+//! | 3 partial sign | `n \|\| X_1..X_n \|\| g_acc < 0 \|\| t_acc \|\| R_1..R_n \|\| m` | `s_i` |
+//! | 4 reveal | `n \|\| X_1..X_n \|\| g_acc < 0 \|\| t_acc \|\| t_1..t_n \|\| m` | `R_i` |
+use crate::key_agg::KeyAggContext;
+use crate::session::{commit_nonce, Session};
+
+use core::fmt::{self, Display, Formatter};
+use jubjub::affine::Affine;
+use jubjub::scalar::Scalar;
+use rand_core::OsRng;
+use schnorr::hash::TaggedHash;
+use schnorr::private::PrivateKey;
+use schnorr::public::PublicKey;
+use schnorr::signature::Signature;
+use std::io::{self, Read, Write};
+use std::process::{Child, ChildStdin, ChildStdout, Command, Stdio};
+use std::sync::{Arc, Mutex};
+
+const PUBLIC_KEY: u8 = 0;
+const SIGN: u8 = 1;
+const NONCE: u8 = 2;
+const PARTIAL_SIGN: u8 = 3;
+const REVEAL: u8 = 4;
+
+const OK: u8 = 0;
+const REJECTED: u8 = 1;
+
+const MAX_FRAME: usize = 1 << 24;
+
+const SESSION: &[u8] = b"MuSig/session";
+
+/// pending nonces a signer keeps
+pub const MAX_NONCES: usize = 64;
+
+#[derive(Debug)]
+pub enum SignerError {
+    Io(io::Error),
+    /// malformed frame or response
+    Protocol(&'static str),
+    /// the signer refused the request
+    Rejected(String),
+}
+
+impl Display for SignerError {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match self {
+            Self::Io(error) => write!(f, "{}", error),
+            Self::Protocol(error) => write!(f, "{}", error),
+            Self::Rejected(reason) => write!(f, "signer rejected the request: {}", reason),
+        }
+    }
+}
+
+impl std::error::Error for SignerError {}
+
+impl From<io::Error> for SignerError {
+    fn from(error: io::Error) -> Self {
+        Self::Io(error)
+    }
+}
+
+pub trait Signer {
+    fn public_key(&mut self) -> Result<PublicKey, SignerError>;
+
+    /// single party schnorr signature
+    fn sign(&mut self, m: &[u8]) -> Result<Signature, SignerError>;
+
+    /// commitment `H_com(R_i)` to a fresh MuSig nonce, the signer keeps `r_i`
+    /// until it's used
+    fn nonce(&mut self) -> Result<[u8; 32], SignerError>;
+
+    /// `R_i` of the signer's commitment among all `commitments`, the nonce is
+    /// bound to them, `context` and `m` from then on
+    fn reveal(
+        &mut self,
+        context: &KeyAggContext,
+        m: &[u8],
+        commitments: &[[u8; 32]],
+    ) -> Result<Affine, SignerError>;
+
+    /// `s_i` with the signer's revealed nonce among `nonces`, which must open
+    /// the commitments it was revealed against under the same `context` and
+    /// `m`, consumes the nonce
+    fn partial_sign(
+        &mut self,
+        context: &KeyAggContext,
+        m: &[u8],
+        nonces: &[Affine],
+    ) -> Result<Scalar, SignerError>;
+}
+
+/// private key held in this process
+pub struct LocalSigner {
+    private_key: PrivateKey,
+    // not used yet, oldest first
+    nonces: Vec<Pending>,
+}
+
+struct Pending {
+    nonce: Affine,
+    r: Scalar,
+    revealed: Option<Revealed>,
+}
+
+struct Revealed {
+    // commitment list `R_i` was revealed against
+    commitments: Vec<[u8; 32]>,
+    // hash of the context and message
+    session: [u8; 32],
+}
+
+impl LocalSigner {
+    pub fn new(private_key: PrivateKey) -> Self {
+        Self {
+            private_key,
+            nonces: Vec::new(),
+        }
+    }
+}
+
+impl Signer for LocalSigner {
+    fn public_key(&mut self) -> Result<PublicKey, SignerError> {
+        Ok(self.private_key.to_public_key())
+    }
+
+    fn sign(&mut self, m: &[u8]) -> Result<Signature, SignerError> {
+        Ok(self.private_key.sign(m, OsRng))
+    }
+
+    fn nonce(&mut self) -> Result<[u8; 32], SignerError> {
+        let r = Scalar::random(OsRng);
+        let nonce = (Affine::basepoint() * r).to_affine();
+        if self.nonces.len() == MAX_NONCES {
+            self.nonces.remove(0);
+        }
+        self.nonces.push(Pending {
+            nonce,
+            r,
+            revealed: None,
+        });
+        Ok(commit_nonce(&nonce))
+    }
+
+    fn reveal(
+        &mut self,
+        context: &KeyAggContext,
+        m: &[u8],
+        commitments: &[[u8; 32]],
+    ) -> Result<Affine, SignerError> {
+        let session = session_hash(context, m);
+        let pending = self
+            .nonces
+            .iter_mut()
+            .find(|pending| commitments.contains(&commit_nonce(&pending.nonce)))
+            .ok_or_else(|| rejected("no commitment of this signer"))?;
+        match &pending.revealed {
+            Some(revealed) if revealed.commitments != commitments => {
+                Err(rejected("nonce was already revealed for other commitments"))
+            }
+            Some(revealed) if revealed.session != session => Err(rejected(
+                "nonce was already revealed for another context or message",
+            )),
+            Some(_) => Ok(pending.nonce),
+            None => {
+                pending.revealed = Some(Revealed {
+                    commitments: commitments.to_vec(),
+                    session,
+                });
+                Ok(pending.nonce)
+            }
+        }
+    }
+
+    fn partial_sign(
+        &mut self,
+        context: &KeyAggContext,
+        m: &[u8],
+        nonces: &[Affine],
+    ) -> Result<Scalar, SignerError> {
+        let public_key = self.private_key.to_public_key();
+        let index = context
+            .keys()
+            .iter()
+            .position(|key| *key == public_key)
+            .ok_or_else(|| rejected("public key is not one of the signers"))?;
+        if nonces.len() != context.keys().len() {
+            return Err(rejected("one nonce per signer is required"));
+        }
+        let position = self
+            .nonces
+            .iter()
+            .position(|pending| pending.nonce == nonces[index])
+            .ok_or_else(|| rejected("nonce is unknown or already used"))?;
+        let revealed = self.nonces[position]
+            .revealed
+            .as_ref()
+            .ok_or_else(|| rejected("nonce was not revealed"))?;
+        if revealed.session != session_hash(context, m) {
+            return Err(rejected(
+                "nonce was revealed for another context or message",
+            ));
+        }
+        if revealed.commitments.len() != nonces.len()
+            || nonces
+                .iter()
+                .zip(&revealed.commitments)
+                .any(|(nonce, commitment)| commit_nonce(nonce) != *commitment)
+        {
+            return Err(rejected("nonces don't match the revealed commitments"));
+        }
+        let r = self.nonces.remove(position).r;
+
+        let session = Session::new(context, m, nonces);
+        Ok(session.cosign(r, self.private_key).unwrap())
+    }
+}
+
+/// one signer shared between threads, such as the connections of a server
+impl<S: Signer> Signer for Arc<Mutex<S>> {
+    fn public_key(&mut self) -> Result<PublicKey, SignerError> {
+        lock(self)?.public_key()
+    }
+
+    fn sign(&mut self, m: &[u8]) -> Result<Signature, SignerError> {
+        lock(self)?.sign(m)
+    }
+
+    fn nonce(&mut self) -> Result<[u8; 32], SignerError> {
+        lock(self)?.nonce()
+    }
+
+    fn reveal(
+        &mut self,
+        context: &KeyAggContext,
+        m: &[u8],
+        commitments: &[[u8; 32]],
+    ) -> Result<Affine, SignerError> {
+        lock(self)?.reveal(context, m, commitments)
+    }
+
+    fn partial_sign(
+        &mut self,
+        context: &KeyAggContext,
+        m: &[u8],
+        nonces: &[Affine],
+    ) -> Result<Scalar, SignerError> {
+        lock(self)?.partial_sign(context, m, nonces)
+    }
+}
+
+/// signer in another process, over a child's stdin and stdout or a socket
+pub struct RemoteSigner<R: Read, W: Write> {
+    reader: R,
+    writer: W,
+    child: Option<Child>,
+}
+
+impl<R: Read, W: Write> RemoteSigner<R, W> {
+    pub fn new(reader: R, writer: W) -> Self {
+        Self {
+            reader,
+            writer,
+            child: None,
+        }
+    }
+
+    fn call(&mut self, op: u8, payload: &[u8]) -> Result<Vec<u8>, SignerError> {
+        write_frame(&mut self.writer, &[&[op], payload].concat())?;
+        let response =
+            read_frame(&mut self.reader)?.ok_or(SignerError::Protocol("signer closed"))?;
+        match response.split_first() {
+            Some((&OK, payload)) => Ok(payload.to_vec()),
+            Some((&REJECTED, reason)) => Err(rejected(&String::from_utf8_lossy(reason))),
+            _ => Err(SignerError::Protocol("malformed response")),
+        }
+    }
+}
+
+impl RemoteSigner<ChildStdout, ChildStdin> {
+    /// runs `command` serving the protocol over its stdin and stdout
+    pub fn spawn(command: &mut Command) -> io::Result<Self> {
+        let mut child = command
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .spawn()?;
+        let reader = child.stdout.take().unwrap();
+        let writer = child.stdin.take().unwrap();
+
+        Ok(Self {
+            reader,
+            writer,
+            child: Some(child),
+        })
+    }
+}
+
+#[cfg(unix)]
+impl RemoteSigner<std::os::unix::net::UnixStream, std::os::unix::net::UnixStream> {
+    pub fn connect(path: impl AsRef<std::path::Path>) -> io::Result<Self> {
+        let stream = std::os::unix::net::UnixStream::connect(path)?;
+        Ok(Self::new(stream.try_clone()?, stream))
+    }
+}
+
+impl<R: Read, W: Write> Drop for RemoteSigner<R, W> {
+    fn drop(&mut self) {
+        if let Some(child) = self.child.as_mut() {
+            let _ = child.kill();
+            let _ = child.wait();
+        }
+    }
+}
+
+impl<R: Read, W: Write> Signer for RemoteSigner<R, W> {
+    fn public_key(&mut self) -> Result<PublicKey, SignerError> {
+        let bytes = self.call(PUBLIC_KEY, &[])?;
+        to_array(&bytes)
+            .and_then(PublicKey::from_bytes)
+            .ok_or(SignerError::Protocol("invalid public key"))
+    }
+
+    fn sign(&mut self, m: &[u8]) -> Result<Signature, SignerError> {
+        let bytes = self.call(SIGN, m)?;
+        to_array(&bytes)
+            .and_then(|bytes| Signature::from_bytes(&bytes))
+            .ok_or(SignerError::Protocol("invalid signature"))
+    }
+
+    fn nonce(&mut self) -> Result<[u8; 32], SignerError> {
+        let bytes = self.call(NONCE, &[])?;
+        to_array(&bytes).ok_or(SignerError::Protocol("invalid commitment"))
+    }
+
+    fn reveal(
+        &mut self,
+        context: &KeyAggContext,
+        m: &[u8],
+        commitments: &[[u8; 32]],
+    ) -> Result<Affine, SignerError> {
+        let request = session_request(context, commitments, m)?;
+
+        let bytes = self.call(REVEAL, &request)?;
+        to_point(&bytes).ok_or(SignerError::Protocol("invalid nonce"))
+    }
+
+    fn partial_sign(
+        &mut self,
+        context: &KeyAggContext,
+        m: &[u8],
+        nonces: &[Affine],
+    ) -> Result<Scalar, SignerError> {
+        let nonces: Vec<[u8; 32]> = nonces.iter().map(|nonce| nonce.to_bytes()).collect();
+        let request = session_request(context, &nonces, m)?;
+
+        let bytes = self.call(PARTIAL_SIGN, &request)?;
+        to_array(&bytes)
+            .and_then(Scalar::from_bytes)
+            .ok_or(SignerError::Protocol("invalid partial signature"))
+    }
+}
+
+/// answers requests with `signer` until the reader is closed
+pub fn serve(
+    signer: &mut impl Signer,
+    mut reader: impl Read,
+    mut writer: impl Write,
+) -> io::Result<()> {
+    while let Some(request) = read_frame(&mut reader).map_err(|error| match error {
+        SignerError::Io(error) => error,
+        error => io::Error::new(io::ErrorKind::InvalidData, error.to_string()),
+    })? {
+        let response = match handle(signer, &request) {
+            Ok(payload) => [&[OK], payload.as_slice()].concat(),
+            Err(error) => [&[REJECTED], error.to_string().as_bytes()].concat(),
+        };
+        write_frame(&mut writer, &response)?;
+    }
+    Ok(())
+}
+
+fn handle(signer: &mut impl Signer, request: &[u8]) -> Result<Vec<u8>, SignerError> {
+    let (op, payload) = request
+        .split_first()
+        .ok_or(SignerError::Protocol("malformed request"))?;
+    match *op {
+        PUBLIC_KEY => Ok(signer.public_key()?.to_bytes().to_vec()),
+        SIGN => Ok(signer.sign(payload)?.to_bytes().to_vec()),
+        NONCE => Ok(signer.nonce()?.to_vec()),
+        REVEAL => {
+            let (context, commitments, m) = parse_session(payload, to_array)
+                .ok_or(SignerError::Protocol("malformed request"))?;
+            Ok(signer
+                .reveal(&context, m, &commitments)?
+                .to_bytes()
+                .to_vec())
+        }
+        PARTIAL_SIGN => {
+            let (context, nonces, m) = parse_session(payload, to_point)
+                .ok_or(SignerError::Protocol("malformed request"))?;
+            Ok(signer
+                .partial_sign(&context, m, &nonces)?
+                .to_bytes()
+                .to_vec())
+        }
+        _ => Err(SignerError::Protocol("unknown request")),
+    }
+}
+
+// `n || X_1..X_n || g_acc < 0 || t_acc || items || m` with one 32 byte item per key
+fn session_request(
+    context: &KeyAggContext,
+    items: &[[u8; 32]],
+    m: &[u8],
+) -> Result<Vec<u8>, SignerError> {
+    let mut request = count(context.keys().len())?.to_le_bytes().to_vec();
+    for key in context.keys() {
+        request.extend_from_slice(&key.to_bytes());
+    }
+    request.push((context.gacc() != Scalar::one()) as u8);
+    request.extend_from_slice(&context.tacc().to_bytes());
+    for item in items {
+        request.extend_from_slice(item);
+    }
+    request.extend_from_slice(m);
+    Ok(request)
+}
+
+fn parse_session<T>(
+    payload: &[u8],
+    item: impl Fn(&[u8]) -> Option<T>,
+) -> Option<(KeyAggContext, Vec<T>, &[u8])> {
+    let n = u32::from_le_bytes(payload.get(..4)?.try_into().unwrap()) as usize;
+    let rest = &payload[4..];
+    if n > rest.len() / 64 {
+        return None;
+    }
+    let (keys, rest) = rest.split_at(n * 32);
+    let keys = keys
+        .chunks(32)
+        .map(|key| to_array(key).and_then(PublicKey::from_bytes))
+        .collect::<Option<Vec<_>>>()?;
+    let (&negated, rest) = rest.split_first()?;
+    let gacc = match negated {
+        0 => Scalar::one(),
+        1 => -Scalar::one(),
+        _ => return None,
+    };
+    let tacc = Scalar::from_bytes(to_array(rest.get(..32)?)?)?;
+    let rest = &rest[32..];
+    if rest.len() < n * 32 {
+        return None;
+    }
+    let (items, m) = rest.split_at(n * 32);
+    let items = items.chunks(32).map(item).collect::<Option<Vec<_>>>()?;
+
+    Some((KeyAggContext::from_parts(&keys, gacc, tacc)?, items, m))
+}
+
+// binds a revealed nonce to the signer set, tweaks and message
+fn session_hash(context: &KeyAggContext, m: &[u8]) -> [u8; 32] {
+    let mut hash = TaggedHash::new(SESSION).chain(&(context.keys().len() as u64).to_le_bytes());
+    for key in context.keys() {
+        hash = hash.chain(&key.to_bytes());
+    }
+    let digest = hash
+        .chain(&context.gacc().to_bytes())
+        .chain(&context.tacc().to_bytes())
+        .chain(m)
+        .finalize_bytes();
+    digest[..32].try_into().unwrap()
+}
+
+// `None` on a clean end of stream before a frame
+fn read_frame(reader: &mut impl Read) -> Result<Option<Vec<u8>>, SignerError> {
+    let mut len = [0; 4];
+    match reader.read_exact(&mut len) {
+        Ok(()) => {}
+        Err(error) if error.kind() == io::ErrorKind::UnexpectedEof => return Ok(None),
+        Err(error) => return Err(error.into()),
+    }
+    let len = u32::from_le_bytes(len) as usize;
+    if len > MAX_FRAME {
+        return Err(SignerError::Protocol("frame is too long"));
+    }
+    let mut body = vec![0; len];
+    reader.read_exact(&mut body)?;
+    Ok(Some(body))
+}
+
+fn write_frame(writer: &mut impl Write, body: &[u8]) -> io::Result<()> {
+    writer.write_all(&(body.len() as u32).to_le_bytes())?;
+    writer.write_all(body)?;
+    writer.flush()
+}
+
+fn to_array<const N: usize>(bytes: &[u8]) -> Option<[u8; N]> {
+    bytes.try_into().ok()
+}
+
+fn to_point(bytes: &[u8]) -> Option<Affine> {
+    to_array(bytes)
+        .and_then(PublicKey::from_bytes)
+        .map(|key| key.to_affine())
+}
+
+fn count(n: usize) -> Result<u32, SignerError> {
+    u32::try_from(n).map_err(|_| SignerError::Protocol("too many items"))
+}
+
+fn lock<S>(signer: &Mutex<S>) -> Result<std::sync::MutexGuard<'_, S>, SignerError> {
+    signer
+        .lock()
+        .map_err(|_| SignerError::Protocol("signer lock is poisoned"))
+}
+
+fn rejected(reason: &str) -> SignerError {
+    SignerError::Rejected(reason.to_string())
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+    use std::os::unix::net::UnixStream;
+    use std::thread;
+
+    fn remote(private_key: PrivateKey) -> RemoteSigner<UnixStream, UnixStream> {
+        let (client, server) = UnixStream::pair().unwrap();
+        thread::spawn(move || {
+            serve(
+                &mut LocalSigner::new(private_key),
+                server.try_clone().unwrap(),
+                server,
+            )
+            .unwrap()
+        });
+        RemoteSigner::new(client.try_clone().unwrap(), client)
+    }
+
+    #[test]
+    fn test_remote_signer() {
+        let private_key = PrivateKey::new(Scalar::random(OsRng));
+        let mut signer = remote(private_key);
+
+        assert_eq!(signer.public_key().unwrap(), private_key.to_public_key());
+        let signature = signer.sign(b"test").unwrap();
+        assert!(private_key.to_public_key().verify(b"test", signature));
+    }
+
+    #[test]
+    fn test_remote_partial_signature() {
+        let private_keys: Vec<PrivateKey> = (0..3)
+            .map(|_| PrivateKey::new(Scalar::random(OsRng)))
+            .collect();
+        let keys: Vec<PublicKey> = private_keys.iter().map(|x| x.to_public_key()).collect();
+        let context = KeyAggContext::new(&keys)
+            .unwrap()
+            .apply_xonly_tweak(Scalar::random(OsRng))
+            .unwrap();
+
+        let mut signers: Vec<Box<dyn Signer>> = vec![
+            Box::new(remote(private_keys[0])),
+            Box::new(LocalSigner::new(private_keys[1])),
+            Box::new(remote(private_keys[2])),
+        ];
+        let commitments: Vec<[u8; 32]> = signers.iter_mut().map(|x| x.nonce().unwrap()).collect();
+        let nonces: Vec<Affine> = signers
+            .iter_mut()
+            .map(|x| x.reveal(&context, b"test", &commitments).unwrap())
+            .collect();
+        let partials: Vec<Scalar> = signers
+            .iter_mut()
+            .map(|x| x.partial_sign(&context, b"test", &nonces).unwrap())
+            .collect();
+
+        let session = Session::new(&context, b"test", &nonces);
+        assert!(session.verify(b"test", session.generate_signature(&partials)));
+        // every nonce is consumed by its partial signature
+        assert!(matches!(
+            signers[0].partial_sign(&context, b"test", &nonces),
+            Err(SignerError::Rejected(_))
+        ));
+        assert!(matches!(
+            signers[1].partial_sign(&context, b"test", &nonces),
+            Err(SignerError::Rejected(_))
+        ));
+    }
+
+    #[test]
+    fn test_commit_then_reveal() {
+        let private_keys: Vec<PrivateKey> = (0..2)
+            .map(|_| PrivateKey::new(Scalar::random(OsRng)))
+            .collect();
+        let keys: Vec<PublicKey> = private_keys.iter().map(|x| x.to_public_key()).collect();
+        let context = KeyAggContext::new(&keys).unwrap();
+        let mut signer = remote(private_keys[0]);
+        let other = Scalar::random(OsRng);
+        let other_nonce = (Affine::basepoint() * other).to_affine();
+
+        let commitments = [signer.nonce().unwrap(), commit_nonce(&other_nonce)];
+        let nonce = signer.reveal(&context, b"test", &commitments).unwrap();
+        assert_eq!(commit_nonce(&nonce), commitments[0]);
+        assert_eq!(
+            signer.reveal(&context, b"test", &commitments).unwrap(),
+            nonce
+        );
+        assert!(signer
+            .reveal(&context, b"test", &[[0; 32], [1; 32]])
+            .is_err());
+
+        // the coordinator picks another nonce for signer 1 after seeing R_0
+        let chosen = (Affine::basepoint() * Scalar::random(OsRng)).to_affine();
+        assert!(signer
+            .reveal(&context, b"test", &[commitments[0], commit_nonce(&chosen)])
+            .is_err());
+        assert!(signer
+            .partial_sign(&context, b"test", &[nonce, chosen])
+            .is_err());
+        assert!(signer
+            .partial_sign(&context, b"test", &[nonce, other_nonce])
+            .is_ok());
+
+        // an unrevealed nonce can't be used
+        let mut local = LocalSigner::new(private_keys[0]);
+        let commitment = local.nonce().unwrap();
+        let pending = local.nonces[0].nonce;
+        assert_eq!(commit_nonce(&pending), commitment);
+        assert!(local
+            .partial_sign(&context, b"test", &[pending, other_nonce])
+            .is_err());
+    }
+
+    #[test]
+    fn test_pending_nonce_cap() {
+        let private_key = PrivateKey::new(Scalar::random(OsRng));
+        let mut signer = LocalSigner::new(private_key);
+        let first = signer.nonce().unwrap();
+        for _ in 0..MAX_NONCES {
+            signer.nonce().unwrap();
+        }
+
+        assert_eq!(signer.nonces.len(), MAX_NONCES);
+        let context = KeyAggContext::new(&[private_key.to_public_key()]).unwrap();
+        assert!(signer.reveal(&context, b"test", &[first]).is_err());
+    }
+
+    #[test]
+    fn test_nonce_bound_to_session() {
+        let private_keys: Vec<PrivateKey> = (0..2)
+            .map(|_| PrivateKey::new(Scalar::random(OsRng)))
+            .collect();
+        let keys: Vec<PublicKey> = private_keys.iter().map(|x| x.to_public_key()).collect();
+        let context = KeyAggContext::new(&keys).unwrap();
+        let tweaked = context.apply_plain_tweak(Scalar::random(OsRng)).unwrap();
+        let mut signer = remote(private_keys[0]);
+        let other_nonce = (Affine::basepoint() * Scalar::random(OsRng)).to_affine();
+
+        let commitments = [signer.nonce().unwrap(), commit_nonce(&other_nonce)];
+        let nonce = signer.reveal(&context, b"test", &commitments).unwrap();
+        assert!(signer.reveal(&context, b"other", &commitments).is_err());
+        assert!(signer.reveal(&tweaked, b"test", &commitments).is_err());
+
+        // the same R_i under another message or tweak would leak x_i
+        let nonces = [nonce, other_nonce];
+        assert!(signer.partial_sign(&context, b"other", &nonces).is_err());
+        assert!(signer.partial_sign(&tweaked, b"test", &nonces).is_err());
+        assert!(signer.partial_sign(&context, b"test", &nonces).is_ok());
+    }
+}
@@ -0,0 +1,130 @@
+//! Simple aggregation over proof of possession verified keys
+//!
+//! With every key's possession proven, the aggregate is the plain sum
+//! `X = sum(X_i)` and no key aggregation coefficients are needed.
+//!
+//! This is the two-round variant without nonce commitments and it is not
+//! secure with concurrent sessions: an adversary who opens many sessions and
+//! picks its nonces after seeing the others' can forge a signature (Drijvers
+//! et al., "On the Security of Two-Round Multi-Signatures"). Only run one
+//! session per key at a time, or exchange `session::commit_nonce`
+//! commitments of every nonce before any nonce is revealed.
+use crate::aggregation::challenge;
+use crate::signature::Signature;
+
+use core::marker::PhantomData;
+use jubjub::affine::Affine;
+use jubjub::extend::Extended;
+use jubjub::scalar::Scalar;
+use schnorr::hash::{ChallengeHash, TaggedHash};
+use schnorr::pop::PopVerifiedKey;
+use schnorr::private::PrivateKey;
+use schnorr::public::PublicKey;
+
+/// two-round signing session, see the module docs before running several at once
+pub struct SimpleParams<H: ChallengeHash = TaggedHash> {
+    // R
+    randomness: Affine,
+    // X
+    public_key: PublicKey,
+    // c
+    challenge: Scalar,
+    hash: PhantomData<H>,
+}
+
+impl SimpleParams {
+    pub fn new(m: &[u8], keys: &[PopVerifiedKey], nonces: &[Affine]) -> Self {
+        Self::with_hash(m, keys, nonces)
+    }
+}
+
+impl<H: ChallengeHash> SimpleParams<H> {
+    pub fn with_hash(m: &[u8], keys: &[PopVerifiedKey], nonces: &[Affine]) -> Self {
+        let randomness = nonces
+            .iter()
+            .fold(Extended::identity(), |acc, r| acc + *r)
+            .to_affine();
+        let public_key = aggregate(keys);
+        let challenge = challenge::<H>(randomness, public_key, m);
+
+        Self {
+            randomness,
+            public_key,
+            challenge,
+            hash: PhantomData,
+        }
+    }
+
+    /// `s_i = r_i + c * x_i`
+    pub fn cosign(&self, r: Scalar, private_key: PrivateKey) -> Scalar {
+        r + private_key * self.challenge
+    }
+
+    pub fn generate_signature(&self, partials: &[Scalar]) -> Signature {
+        let s = partials.iter().fold(Scalar::zero(), |acc, s| acc + *s);
+
+        Signature::new(self.randomness, s)
+    }
+
+    pub fn public_key(&self) -> PublicKey {
+        self.public_key
+    }
+
+    pub fn verify(&self, m: &[u8], sig: Signature) -> bool {
+        let r = sig.get_r();
+        let s = sig.get_s();
+        let challenge = challenge::<H>(r, self.public_key, m);
+        let rc = r + self.public_key * challenge;
+        let gs = Affine::basepoint() * s;
+
+        rc.to_affine() == gs.to_affine()
+    }
+}
+
+/// `X = sum(X_i)`
+pub fn aggregate(keys: &[PopVerifiedKey]) -> PublicKey {
+    let sum = keys.iter().fold(Extended::identity(), |acc, key| {
+        acc + key.public_key().to_affine()
+    });
+    PublicKey::new(sum.to_affine())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use proptest::{collection::vec, prelude::*};
+    use rand_core::OsRng;
+
+    prop_compose! {
+        fn arb_field()(
+            bytes in vec(any::<u8>(), 64)
+        ) -> Scalar {
+            Scalar::from_bytes_wide(&<[u8; 64]>::try_from(bytes).unwrap())
+        }
+    }
+
+    proptest! {
+        #![proptest_config(ProptestConfig::with_cases(10))]
+        #[test]
+        fn test_simple_aggregation(keys in vec(arb_field(), 1..5)) {
+            let message = b"test";
+            let private_keys: Vec<PrivateKey> = keys.into_iter().map(PrivateKey::new).collect();
+            let verified: Vec<PopVerifiedKey> = private_keys
+                .iter()
+                .map(|key| PopVerifiedKey::new(key.to_public_key(), &key.prove_possession(OsRng)).unwrap())
+                .collect();
+            let r: Vec<Scalar> = private_keys.iter().map(|_| Scalar::random(OsRng)).collect();
+            let nonces: Vec<Affine> = r.iter().map(|r| (Affine::basepoint() * *r).to_affine()).collect();
+
+            let params = SimpleParams::new(message, &verified, &nonces);
+            let partials: Vec<Scalar> = private_keys
+                .iter()
+                .zip(r)
+                .map(|(key, r)| params.cosign(r, *key))
+                .collect();
+            let signature = params.generate_signature(&partials);
+
+            assert!(params.verify(message, signature))
+        }
+    }
+}
@@ -0,0 +1,508 @@
+//! Deterministic in-memory network for multi-party tests
+//!
+//! Signers run as tasks of one single threaded executor on a simulated
+//! clock. Every broadcast is copied to each other endpoint, dropped or
+//! delayed by a seeded generator, and may be rewritten per recipient to model
+//! malicious participants. Time only advances when every task is blocked, to
+//! the next delivery or timer deadline, so a run is reproducible from its seed.
+use crate::driver::Timer;
+use crate::wire::Message;
+
+use core::convert::Infallible;
+use core::future::Future;
+use core::pin::Pin;
+use core::task::{Context, Poll, Waker};
+use core::time::Duration;
+use futures::executor::LocalPool;
+use futures::task::LocalSpawnExt;
+use futures::{Sink, Stream};
+use std::cell::RefCell;
+use std::collections::{BTreeMap, VecDeque};
+use std::rc::Rc;
+
+/// rewrites a message from its sender to recipient `to`, `None` drops it
+pub type Tamper = Box<dyn FnMut(u32, Message) -> Option<Message>>;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct NetworkConfig {
+    pub min_delay: Duration,
+    pub max_delay: Duration,
+    /// dropped messages per thousand
+    pub drop_rate: u32,
+    pub seed: u64,
+}
+
+impl Default for NetworkConfig {
+    fn default() -> Self {
+        Self {
+            min_delay: Duration::from_millis(10),
+            max_delay: Duration::from_millis(100),
+            drop_rate: 0,
+            seed: 0,
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct Network(Rc<RefCell<Shared>>);
+
+struct Shared {
+    config: NetworkConfig,
+    rand: u64,
+    now: Duration,
+    // ordered by (time, sequence) so ties resolve in send order
+    seq: u64,
+    // (to, from, message)
+    deliveries: BTreeMap<(Duration, u64), (u32, u32, Message)>,
+    timers: BTreeMap<(Duration, u64), Waker>,
+    inboxes: Vec<VecDeque<(u32, Message)>>,
+    wakers: Vec<Option<Waker>>,
+    tampers: Vec<Option<Tamper>>,
+}
+
+/// `Sink` broadcasting to every other endpoint and `Stream` of received
+/// messages paired with the index of the endpoint that sent them
+pub struct Endpoint {
+    network: Network,
+    index: u32,
+}
+
+pub struct SimTimer(Network);
+
+pub struct Sleep {
+    network: Network,
+    deadline: Duration,
+    // entry in the timers while pending
+    key: Option<(Duration, u64)>,
+}
+
+impl Network {
+    /// panics if `min_delay` exceeds `max_delay`
+    pub fn new(n: usize, config: NetworkConfig) -> Self {
+        assert!(
+            config.min_delay <= config.max_delay,
+            "min_delay exceeds max_delay"
+        );
+        Self(Rc::new(RefCell::new(Shared {
+            config,
+            rand: config.seed,
+            now: Duration::ZERO,
+            seq: 0,
+            deliveries: BTreeMap::new(),
+            timers: BTreeMap::new(),
+            inboxes: vec![VecDeque::new(); n],
+            wakers: vec![None; n],
+            tampers: (0..n).map(|_| None).collect(),
+        })))
+    }
+
+    pub fn endpoint(&self, index: u32) -> Endpoint {
+        Endpoint {
+            network: self.clone(),
+            index,
+        }
+    }
+
+    pub fn timer(&self) -> SimTimer {
+        SimTimer(self.clone())
+    }
+
+    pub fn now(&self) -> Duration {
+        self.0.borrow().now
+    }
+
+    /// applies `tamper` to every message sent by `index`
+    pub fn tamper(
+        &self,
+        index: u32,
+        tamper: impl FnMut(u32, Message) -> Option<Message> + 'static,
+    ) {
+        self.0.borrow_mut().tampers[index as usize] = Some(Box::new(tamper));
+    }
+
+    /// runs the tasks to completion, `None` for a task still blocked when
+    /// nothing is left to deliver or wake
+    pub fn run<F>(&self, tasks: Vec<F>) -> Vec<Option<F::Output>>
+    where
+        F: Future + 'static,
+        F::Output: 'static,
+    {
+        let mut pool = LocalPool::new();
+        let outputs = Rc::new(RefCell::new(
+            (0..tasks.len()).map(|_| None).collect::<Vec<_>>(),
+        ));
+        for (i, task) in tasks.into_iter().enumerate() {
+            let outputs = outputs.clone();
+            pool.spawner()
+                .spawn_local(async move {
+                    let output = task.await;
+                    outputs.borrow_mut()[i] = Some(output);
+                })
+                .unwrap();
+        }
+
+        loop {
+            pool.run_until_stalled();
+            if outputs.borrow().iter().all(Option::is_some) || !self.advance() {
+                break;
+            }
+        }
+        drop(pool);
+        Rc::try_unwrap(outputs).ok().unwrap().into_inner()
+    }
+
+    // moves the clock to the next event, false if there is none
+    fn advance(&self) -> bool {
+        let mut shared = self.0.borrow_mut();
+        let next = [shared.deliveries.keys().next(), shared.timers.keys().next()]
+            .into_iter()
+            .flatten()
+            .map(|(time, _)| *time)
+            .min();
+        let Some(now) = next else {
+            return false;
+        };
+        shared.now = now;
+
+        while let Some(entry) = shared.deliveries.first_entry() {
+            if entry.key().0 > now {
+                break;
+            }
+            let (to, from, message) = entry.remove();
+            shared.inboxes[to as usize].push_back((from, message));
+            if let Some(waker) = shared.wakers[to as usize].take() {
+                waker.wake();
+            }
+        }
+        while let Some(entry) = shared.timers.first_entry() {
+            if entry.key().0 > now {
+                break;
+            }
+            entry.remove().wake();
+        }
+        true
+    }
+}
+
+impl Shared {
+    fn broadcast(&mut self, from: u32, message: Message) {
+        let mut tamper = self.tampers[from as usize].take();
+        for to in 0..self.inboxes.len() as u32 {
+            if to == from {
+                continue;
+            }
+            let message = match tamper.as_mut() {
+                Some(tamper) => tamper(to, message.clone()),
+                None => Some(message.clone()),
+            };
+            if self.next_rand() % 1000 < self.config.drop_rate as u64 {
+                continue;
+            }
+            let spread = (self.config.max_delay - self.config.min_delay).as_nanos() as u64;
+            let delay =
+                self.config.min_delay + Duration::from_nanos(self.next_rand() % (spread + 1));
+            if let Some(message) = message {
+                let key = (self.now + delay, self.next_seq());
+                self.deliveries.insert(key, (to, from, message));
+            }
+        }
+        self.tampers[from as usize] = tamper;
+    }
+
+    // splitmix64
+    fn next_rand(&mut self) -> u64 {
+        self.rand = self.rand.wrapping_add(0x9e3779b97f4a7c15);
+        let mut z = self.rand;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58476d1ce4e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d049bb133111eb);
+        z ^ (z >> 31)
+    }
+
+    fn next_seq(&mut self) -> u64 {
+        self.seq += 1;
+        self.seq
+    }
+}
+
+impl Sink<Message> for Endpoint {
+    type Error = Infallible;
+
+    fn poll_ready(self: Pin<&mut Self>, _: &mut Context) -> Poll<Result<(), Infallible>> {
+        Poll::Ready(Ok(()))
+    }
+
+    fn start_send(self: Pin<&mut Self>, message: Message) -> Result<(), Infallible> {
+        self.network.0.borrow_mut().broadcast(self.index, message);
+        Ok(())
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, _: &mut Context) -> Poll<Result<(), Infallible>> {
+        Poll::Ready(Ok(()))
+    }
+
+    fn poll_close(self: Pin<&mut Self>, _: &mut Context) -> Poll<Result<(), Infallible>> {
+        Poll::Ready(Ok(()))
+    }
+}
+
+impl Stream for Endpoint {
+    type Item = (u32, Message);
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context) -> Poll<Option<(u32, Message)>> {
+        let mut shared = self.network.0.borrow_mut();
+        let index = self.index as usize;
+        match shared.inboxes[index].pop_front() {
+            Some(message) => Poll::Ready(Some(message)),
+            None => {
+                shared.wakers[index] = Some(cx.waker().clone());
+                Poll::Pending
+            }
+        }
+    }
+}
+
+impl Timer for SimTimer {
+    type Sleep = Sleep;
+
+    fn sleep(&self, duration: Duration) -> Sleep {
+        Sleep {
+            network: self.0.clone(),
+            deadline: self.0.now() + duration,
+            key: None,
+        }
+    }
+}
+
+impl Future for Sleep {
+    type Output = ();
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context) -> Poll<()> {
+        let network = self.network.clone();
+        let mut shared = network.0.borrow_mut();
+        if shared.now >= self.deadline {
+            if let Some(key) = self.key.take() {
+                shared.timers.remove(&key);
+            }
+            return Poll::Ready(());
+        }
+        let key = match self.key {
+            Some(key) => key,
+            None => {
+                let key = (self.deadline, shared.next_seq());
+                self.key = Some(key);
+                key
+            }
+        };
+        shared.timers.insert(key, cx.waker().clone());
+        Poll::Pending
+    }
+}
+
+impl Drop for Sleep {
+    fn drop(&mut self) {
+        if let Some(key) = self.key.take() {
+            self.network.0.borrow_mut().timers.remove(&key);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::driver::{Abort, Driver, DriverConfig, Round};
+    use crate::key_agg::KeyAggContext;
+    use crate::signature::Signature;
+    use crate::signer::LocalSigner;
+    use crate::wire::Payload;
+    use futures::SinkExt;
+    use jubjub::affine::Affine;
+    use jubjub::scalar::Scalar;
+    use rand_core::OsRng;
+    use schnorr::private::PrivateKey;
+
+    const MESSAGE: &[u8] = b"test";
+
+    type Task = Pin<Box<dyn Future<Output = Result<Signature, Abort>>>>;
+
+    // drivers of every signer except `skip`
+    fn session(n: usize, network: &Network, skip: &[u32]) -> Vec<Task> {
+        let private_keys: Vec<PrivateKey> = (0..n)
+            .map(|_| PrivateKey::new(Scalar::random(OsRng)))
+            .collect();
+        let keys: Vec<_> = private_keys.iter().map(|x| x.to_public_key()).collect();
+        let context = KeyAggContext::new(&keys).unwrap();
+
+        private_keys
+            .into_iter()
+            .enumerate()
+            .filter(|(i, _)| !skip.contains(&(*i as u32)))
+            .map(|(_, private_key)| {
+                let driver = Driver::new(
+                    &context,
+                    LocalSigner::new(private_key),
+                    [1; 32],
+                    MESSAGE,
+                    DriverConfig::default(),
+                )
+                .unwrap();
+                let sink = network.endpoint(driver.index());
+                let stream = network.endpoint(driver.index());
+                let timer = network.timer();
+                Box::pin(async move { driver.run(sink, stream, &timer).await }) as Task
+            })
+            .collect()
+    }
+
+    fn run_session(
+        n: usize,
+        network: &Network,
+        skip: &[u32],
+    ) -> Vec<Option<Result<Signature, Abort>>> {
+        network.run(session(n, network, skip))
+    }
+
+    #[test]
+    fn test_honest_session() {
+        let network = Network::new(3, NetworkConfig::default());
+        let results = run_session(3, &network, &[]);
+
+        let signatures: Vec<Signature> = results.into_iter().map(|r| r.unwrap().unwrap()).collect();
+        assert!(signatures.iter().all(|sig| *sig == signatures[0]));
+    }
+
+    #[test]
+    fn test_lossy_session() {
+        let config = NetworkConfig {
+            drop_rate: 200,
+            seed: 7,
+            ..NetworkConfig::default()
+        };
+        let network = Network::new(4, config);
+        let results = run_session(4, &network, &[]);
+
+        assert!(results.into_iter().all(|r| r.unwrap().is_ok()));
+        // at least one round only completed after a retransmission
+        assert!(network.now() > DriverConfig::default().timeout);
+    }
+
+    #[test]
+    fn test_silent_signer() {
+        let network = Network::new(3, NetworkConfig::default());
+        let results = run_session(3, &network, &[2]);
+
+        for result in results {
+            assert_eq!(
+                result.unwrap(),
+                Err(Abort::Timeout {
+                    round: Round::Commitment,
+                    missing: vec![2]
+                })
+            );
+        }
+        assert_eq!(network.now(), DriverConfig::default().timeout * 4);
+    }
+
+    #[test]
+    fn test_malicious_signers() {
+        let network = Network::new(3, NetworkConfig::default());
+        network.tamper(2, |_, mut message| {
+            if let Payload::PartialSignature { s } = &mut message.payload {
+                *s = *s + Scalar::one();
+            }
+            Some(message)
+        });
+        for result in run_session(3, &network, &[]).into_iter().take(2) {
+            assert_eq!(
+                result.unwrap(),
+                Err(Abort::InvalidPartialSignature { signer: 2 })
+            );
+        }
+
+        let network = Network::new(3, NetworkConfig::default());
+        network.tamper(1, |_, mut message| {
+            if let Payload::NonceReveal { nonce } = &mut message.payload {
+                *nonce = (*nonce + Affine::basepoint()).to_affine();
+            }
+            Some(message)
+        });
+        let results = run_session(3, &network, &[]);
+        assert_eq!(
+            results[0].clone().unwrap(),
+            Err(Abort::InvalidNonce { signer: 1 })
+        );
+    }
+
+    #[test]
+    fn test_spoofed_sender() {
+        // signer 2 claims to be signer 0, which must not be blamed
+        let network = Network::new(3, NetworkConfig::default());
+        network.tamper(2, |_, mut message| {
+            message.signer = 0;
+            if let Payload::NonceCommitment { commitment } = &mut message.payload {
+                commitment[0] ^= 1;
+            }
+            Some(message)
+        });
+
+        for result in run_session(3, &network, &[]).into_iter().take(2) {
+            assert_eq!(
+                result.unwrap(),
+                Err(Abort::Timeout {
+                    round: Round::Commitment,
+                    missing: vec![2]
+                })
+            );
+        }
+    }
+
+    #[test]
+    fn test_sleep_timer_entries() {
+        let network = Network::new(1, NetworkConfig::default());
+        let mut sleep = Box::pin(network.timer().sleep(Duration::from_secs(1)));
+        let waker = futures::task::noop_waker();
+        let mut cx = Context::from_waker(&waker);
+
+        for _ in 0..3 {
+            assert!(sleep.as_mut().poll(&mut cx).is_pending());
+        }
+        assert_eq!(network.0.borrow().timers.len(), 1);
+        drop(sleep);
+        assert!(network.0.borrow().timers.is_empty());
+    }
+
+    #[test]
+    #[should_panic(expected = "min_delay exceeds max_delay")]
+    fn test_invalid_delays() {
+        let config = NetworkConfig {
+            min_delay: Duration::from_millis(100),
+            max_delay: Duration::from_millis(10),
+            ..NetworkConfig::default()
+        };
+        Network::new(2, config);
+    }
+
+    #[test]
+    fn test_equivocation() {
+        let network = Network::new(3, NetworkConfig::default());
+        let mut endpoint = network.endpoint(2);
+        let commitments = [[2; 32], [3; 32]];
+        let equivocate = async move {
+            for commitment in commitments {
+                let payload = Payload::NonceCommitment { commitment };
+                endpoint
+                    .send(Message::new([1; 32], 2, payload))
+                    .await
+                    .unwrap();
+            }
+            Err(Abort::Disconnected)
+        };
+
+        let mut tasks: Vec<Task> = vec![Box::pin(equivocate)];
+        tasks.extend(session(3, &network, &[2]));
+        let results = network.run(tasks);
+        assert_eq!(
+            results[1].clone().unwrap(),
+            Err(Abort::Equivocation { signer: 2 })
+        );
+    }
+}
@@ -0,0 +1,634 @@
+//! Versioned wire messages of the signing rounds
+//!
+//! Every message carries the format version, the session id and the index of
+//! the sending signer. The compact binary layout is
+//! `version || kind || session_id || signer (u32 le) || payload`, the CBOR and
+//! JSON encodings are self-describing and write byte strings as hex in JSON.
+use crate::key_agg::KeyAggContext;
+use crate::signature::Signature;
+
+use core::fmt::{self, Display, Formatter};
+use jubjub::affine::Affine;
+use jubjub::scalar::Scalar;
+use schnorr::public::PublicKey;
+use serde::de::{self, Deserializer, SeqAccess, Visitor};
+use serde::ser::Serializer;
+use serde::{Deserialize, Serialize};
+
+pub const VERSION: u8 = 1;
+
+const NONCE_COMMITMENT: u8 = 0;
+const NONCE_REVEAL: u8 = 1;
+const PARTIAL_SIGNATURE: u8 = 2;
+const SIGNATURE: u8 = 3;
+const KEY_AGG: u8 = 4;
+
+const PLAIN_TWEAK: u8 = 0;
+const XONLY_TWEAK: u8 = 1;
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum WireError {
+    UnsupportedVersion(u8),
+    UnknownKind(u8),
+    Truncated,
+    TrailingBytes,
+    InvalidPoint,
+    InvalidScalar,
+    InvalidContext,
+    TooManyItems,
+    Encoding(String),
+}
+
+impl Display for WireError {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match self {
+            Self::UnsupportedVersion(version) => write!(f, "unsupported version {}", version),
+            Self::UnknownKind(kind) => write!(f, "unknown message kind {}", kind),
+            Self::Truncated => write!(f, "message is truncated"),
+            Self::TrailingBytes => write!(f, "trailing bytes after message"),
+            Self::InvalidPoint => write!(f, "point is not in the prime order subgroup"),
+            Self::InvalidScalar => write!(f, "scalar is not canonical"),
+            Self::InvalidContext => write!(f, "key aggregation context is invalid"),
+            Self::TooManyItems => write!(f, "item count doesn't fit in a u32"),
+            Self::Encoding(error) => write!(f, "{}", error),
+        }
+    }
+}
+
+impl std::error::Error for WireError {}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Message {
+    pub version: u8,
+    #[serde(with = "array")]
+    pub session_id: [u8; 32],
+    pub signer: u32,
+    pub payload: Payload,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(
+    tag = "type",
+    content = "body",
+    rename_all = "snake_case",
+    deny_unknown_fields
+)]
+pub enum Payload {
+    /// `t_i = H_com(R_i)`
+    NonceCommitment {
+        #[serde(with = "array")]
+        commitment: [u8; 32],
+    },
+    /// `R_i`
+    NonceReveal {
+        #[serde(with = "point")]
+        nonce: Affine,
+    },
+    /// `s_i`
+    PartialSignature {
+        #[serde(with = "scalar")]
+        s: Scalar,
+    },
+    /// `(R, s)`
+    Signature {
+        #[serde(with = "signature")]
+        signature: Signature,
+    },
+    KeyAgg(KeyAggMessage),
+}
+
+// read ahead of the message so a newer version is reported as such
+#[derive(Deserialize)]
+struct Versioned {
+    version: u8,
+}
+
+/// signer keys in order and the tweaks applied to their aggregate
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct KeyAggMessage {
+    #[serde(with = "keys")]
+    pub keys: Vec<PublicKey>,
+    pub tweaks: Vec<Tweak>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case", deny_unknown_fields)]
+pub enum Tweak {
+    Plain {
+        #[serde(with = "scalar")]
+        tweak: Scalar,
+    },
+    XOnly {
+        #[serde(with = "scalar")]
+        tweak: Scalar,
+    },
+}
+
+impl KeyAggMessage {
+    /// replays the tweaks, `None` if the context is invalid
+    pub fn to_context(&self) -> Option<KeyAggContext> {
+        self.tweaks.iter().try_fold(
+            KeyAggContext::new(&self.keys)?,
+            |context, tweak| match tweak {
+                Tweak::Plain { tweak } => context.apply_plain_tweak(*tweak),
+                Tweak::XOnly { tweak } => context.apply_xonly_tweak(*tweak),
+            },
+        )
+    }
+}
+
+impl Message {
+    pub fn new(session_id: [u8; 32], signer: u32, payload: Payload) -> Self {
+        Self {
+            version: VERSION,
+            session_id,
+            signer,
+            payload,
+        }
+    }
+
+    /// fails if a key aggregation message has more than `u32::MAX` items
+    pub fn to_bytes(&self) -> Result<Vec<u8>, WireError> {
+        let mut bytes = vec![self.version, self.payload.kind()];
+        bytes.extend_from_slice(&self.session_id);
+        bytes.extend_from_slice(&self.signer.to_le_bytes());
+        match &self.payload {
+            Payload::NonceCommitment { commitment } => bytes.extend_from_slice(commitment),
+            Payload::NonceReveal { nonce } => bytes.extend_from_slice(&nonce.to_bytes()),
+            Payload::PartialSignature { s } => bytes.extend_from_slice(&s.to_bytes()),
+            Payload::Signature { signature } => bytes.extend_from_slice(&signature.to_bytes()),
+            Payload::KeyAgg(message) => {
+                bytes.extend_from_slice(&count(message.keys.len())?.to_le_bytes());
+                for key in &message.keys {
+                    bytes.extend_from_slice(&key.to_bytes());
+                }
+                bytes.extend_from_slice(&count(message.tweaks.len())?.to_le_bytes());
+                for tweak in &message.tweaks {
+                    let (kind, t) = match tweak {
+                        Tweak::Plain { tweak } => (PLAIN_TWEAK, tweak),
+                        Tweak::XOnly { tweak } => (XONLY_TWEAK, tweak),
+                    };
+                    bytes.push(kind);
+                    bytes.extend_from_slice(&t.to_bytes());
+                }
+            }
+        }
+        Ok(bytes)
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, WireError> {
+        let mut reader = Reader(bytes);
+        let version = reader.byte()?;
+        check_version(version)?;
+        let kind = reader.byte()?;
+        let session_id = reader.array()?;
+        let signer = u32::from_le_bytes(reader.array()?);
+        let payload = match kind {
+            NONCE_COMMITMENT => Payload::NonceCommitment {
+                commitment: reader.array()?,
+            },
+            NONCE_REVEAL => Payload::NonceReveal {
+                nonce: to_point(reader.array()?)?,
+            },
+            PARTIAL_SIGNATURE => Payload::PartialSignature {
+                s: to_scalar(reader.array()?)?,
+            },
+            SIGNATURE => Payload::Signature {
+                signature: to_signature(&reader.array()?)?,
+            },
+            KEY_AGG => {
+                let n = reader.len(32)?;
+                let keys = (0..n)
+                    .map(|_| to_key(reader.array()?))
+                    .collect::<Result<_, _>>()?;
+                let n = reader.len(33)?;
+                let tweaks = (0..n)
+                    .map(|_| {
+                        let kind = reader.byte()?;
+                        let tweak = to_scalar(reader.array()?)?;
+                        match kind {
+                            PLAIN_TWEAK => Ok(Tweak::Plain { tweak }),
+                            XONLY_TWEAK => Ok(Tweak::XOnly { tweak }),
+                            _ => Err(WireError::UnknownKind(kind)),
+                        }
+                    })
+                    .collect::<Result<_, _>>()?;
+                Payload::KeyAgg(KeyAggMessage { keys, tweaks })
+            }
+            _ => return Err(WireError::UnknownKind(kind)),
+        };
+        if !reader.0.is_empty() {
+            return Err(WireError::TrailingBytes);
+        }
+
+        Self {
+            version,
+            session_id,
+            signer,
+            payload,
+        }
+        .validate()
+    }
+
+    pub fn to_cbor(&self) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        ciborium::into_writer(self, &mut bytes).unwrap();
+        bytes
+    }
+
+    pub fn from_cbor(bytes: &[u8]) -> Result<Self, WireError> {
+        let versioned: Versioned =
+            ciborium::from_reader(bytes).map_err(|error| WireError::Encoding(error.to_string()))?;
+        check_version(versioned.version)?;
+        let mut reader = bytes;
+        let message: Self = ciborium::from_reader(&mut reader)
+            .map_err(|error| WireError::Encoding(error.to_string()))?;
+        if !reader.is_empty() {
+            return Err(WireError::TrailingBytes);
+        }
+        message.validate()
+    }
+
+    pub fn to_json(&self) -> String {
+        serde_json::to_string(self).unwrap()
+    }
+
+    pub fn from_json(json: &str) -> Result<Self, WireError> {
+        let versioned: Versioned =
+            serde_json::from_str(json).map_err(|error| WireError::Encoding(error.to_string()))?;
+        check_version(versioned.version)?;
+        let message: Self =
+            serde_json::from_str(json).map_err(|error| WireError::Encoding(error.to_string()))?;
+        message.validate()
+    }
+
+    fn validate(self) -> Result<Self, WireError> {
+        check_version(self.version)?;
+        if let Payload::KeyAgg(message) = &self.payload {
+            message.to_context().ok_or(WireError::InvalidContext)?;
+        }
+        Ok(self)
+    }
+}
+
+impl Payload {
+    fn kind(&self) -> u8 {
+        match self {
+            Self::NonceCommitment { .. } => NONCE_COMMITMENT,
+            Self::NonceReveal { .. } => NONCE_REVEAL,
+            Self::PartialSignature { .. } => PARTIAL_SIGNATURE,
+            Self::Signature { .. } => SIGNATURE,
+            Self::KeyAgg(_) => KEY_AGG,
+        }
+    }
+}
+
+struct Reader<'a>(&'a [u8]);
+
+impl Reader<'_> {
+    fn byte(&mut self) -> Result<u8, WireError> {
+        Ok(self.array::<1>()?[0])
+    }
+
+    fn array<const N: usize>(&mut self) -> Result<[u8; N], WireError> {
+        if self.0.len() < N {
+            return Err(WireError::Truncated);
+        }
+        let (head, tail) = self.0.split_at(N);
+        self.0 = tail;
+        Ok(head.try_into().unwrap())
+    }
+
+    // item count, rejected before allocating if the items can't fit
+    fn len(&mut self, item: usize) -> Result<usize, WireError> {
+        let n = u32::from_le_bytes(self.array()?) as usize;
+        if n > self.0.len() / item {
+            return Err(WireError::Truncated);
+        }
+        Ok(n)
+    }
+}
+
+fn check_version(version: u8) -> Result<(), WireError> {
+    if version != VERSION {
+        return Err(WireError::UnsupportedVersion(version));
+    }
+    Ok(())
+}
+
+fn count(n: usize) -> Result<u32, WireError> {
+    u32::try_from(n).map_err(|_| WireError::TooManyItems)
+}
+
+fn to_key(bytes: [u8; 32]) -> Result<PublicKey, WireError> {
+    PublicKey::from_bytes(bytes).ok_or(WireError::InvalidPoint)
+}
+
+fn to_point(bytes: [u8; 32]) -> Result<Affine, WireError> {
+    to_key(bytes).map(|key| key.to_affine())
+}
+
+fn to_scalar(bytes: [u8; 32]) -> Result<Scalar, WireError> {
+    Scalar::from_bytes(bytes).ok_or(WireError::InvalidScalar)
+}
+
+fn to_signature(bytes: &[u8; 64]) -> Result<Signature, WireError> {
+    let r = to_point(bytes[..32].try_into().unwrap())?;
+    let s = to_scalar(bytes[32..].try_into().unwrap())?;
+    Ok(Signature::new(r, s))
+}
+
+// hex string in human readable formats, byte string otherwise
+fn serialize_bytes<S: Serializer>(bytes: &[u8], serializer: S) -> Result<S::Ok, S::Error> {
+    if serializer.is_human_readable() {
+        serializer.serialize_str(&hex::encode(bytes))
+    } else {
+        serializer.serialize_bytes(bytes)
+    }
+}
+
+fn deserialize_array<'de, D: Deserializer<'de>, const N: usize>(
+    deserializer: D,
+) -> Result<[u8; N], D::Error> {
+    let Bytes(bytes) = Bytes::deserialize(deserializer)?;
+    bytes.try_into().map_err(|bytes: Vec<u8>| {
+        de::Error::invalid_length(bytes.len(), &"a fixed size byte string")
+    })
+}
+
+struct Bytes(Vec<u8>);
+
+impl<'de> Deserialize<'de> for Bytes {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        if deserializer.is_human_readable() {
+            deserializer.deserialize_str(BytesVisitor)
+        } else {
+            deserializer.deserialize_bytes(BytesVisitor)
+        }
+    }
+}
+
+struct BytesVisitor;
+
+impl<'de> Visitor<'de> for BytesVisitor {
+    type Value = Bytes;
+
+    fn expecting(&self, f: &mut Formatter) -> fmt::Result {
+        write!(f, "a hex or byte string")
+    }
+
+    fn visit_str<E: de::Error>(self, value: &str) -> Result<Bytes, E> {
+        hex::decode(value).map(Bytes).map_err(E::custom)
+    }
+
+    fn visit_bytes<E: de::Error>(self, value: &[u8]) -> Result<Bytes, E> {
+        Ok(Bytes(value.to_vec()))
+    }
+}
+
+mod array {
+    use super::*;
+
+    pub fn serialize<S: Serializer>(bytes: &[u8; 32], serializer: S) -> Result<S::Ok, S::Error> {
+        serialize_bytes(bytes, serializer)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<[u8; 32], D::Error> {
+        deserialize_array(deserializer)
+    }
+}
+
+mod point {
+    use super::*;
+
+    pub fn serialize<S: Serializer>(point: &Affine, serializer: S) -> Result<S::Ok, S::Error> {
+        serialize_bytes(&point.to_bytes(), serializer)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Affine, D::Error> {
+        to_point(deserialize_array(deserializer)?).map_err(de::Error::custom)
+    }
+}
+
+mod scalar {
+    use super::*;
+
+    pub fn serialize<S: Serializer>(scalar: &Scalar, serializer: S) -> Result<S::Ok, S::Error> {
+        serialize_bytes(&scalar.to_bytes(), serializer)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Scalar, D::Error> {
+        to_scalar(deserialize_array(deserializer)?).map_err(de::Error::custom)
+    }
+}
+
+mod signature {
+    use super::*;
+
+    pub fn serialize<S: Serializer>(sig: &Signature, serializer: S) -> Result<S::Ok, S::Error> {
+        serialize_bytes(&sig.to_bytes(), serializer)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Signature, D::Error> {
+        to_signature(&deserialize_array(deserializer)?).map_err(de::Error::custom)
+    }
+}
+
+mod keys {
+    use super::*;
+    use serde::ser::SerializeSeq;
+
+    pub fn serialize<S: Serializer>(keys: &[PublicKey], serializer: S) -> Result<S::Ok, S::Error> {
+        let mut seq = serializer.serialize_seq(Some(keys.len()))?;
+        for key in keys {
+            seq.serialize_element(&Key(*key))?;
+        }
+        seq.end()
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Vec<PublicKey>, D::Error> {
+        deserializer.deserialize_seq(KeysVisitor)
+    }
+
+    struct Key(PublicKey);
+
+    impl Serialize for Key {
+        fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+            serialize_bytes(&self.0.to_bytes(), serializer)
+        }
+    }
+
+    impl<'de> Deserialize<'de> for Key {
+        fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+            to_key(deserialize_array(deserializer)?)
+                .map(Key)
+                .map_err(de::Error::custom)
+        }
+    }
+
+    struct KeysVisitor;
+
+    impl<'de> Visitor<'de> for KeysVisitor {
+        type Value = Vec<PublicKey>;
+
+        fn expecting(&self, f: &mut Formatter) -> fmt::Result {
+            write!(f, "a sequence of public keys")
+        }
+
+        fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Self::Value, A::Error> {
+            let mut keys = Vec::new();
+            while let Some(Key(key)) = seq.next_element()? {
+                keys.push(key);
+            }
+            Ok(keys)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::session::commit_nonce;
+    use proptest::{collection::vec, prelude::*};
+    use schnorr::private::PrivateKey;
+
+    prop_compose! {
+        fn arb_field()(
+            bytes in vec(any::<u8>(), 64)
+        ) -> Scalar {
+            Scalar::from_bytes_wide(&<[u8; 64]>::try_from(bytes).unwrap())
+        }
+    }
+
+    fn messages(r: Scalar, x: Scalar, y: Scalar) -> Vec<Message> {
+        let nonce = (Affine::basepoint() * r).to_affine();
+        let keys = vec![
+            PrivateKey::new(x).to_public_key(),
+            PrivateKey::new(y).to_public_key(),
+        ];
+        let session_id = r.to_bytes();
+        vec![
+            Payload::NonceCommitment {
+                commitment: commit_nonce(&nonce),
+            },
+            Payload::NonceReveal { nonce },
+            Payload::PartialSignature { s: x },
+            Payload::Signature {
+                signature: Signature::new(nonce, y),
+            },
+            Payload::KeyAgg(KeyAggMessage {
+                keys,
+                tweaks: vec![Tweak::Plain { tweak: x }, Tweak::XOnly { tweak: y }],
+            }),
+        ]
+        .into_iter()
+        .enumerate()
+        .map(|(i, payload)| Message::new(session_id, i as u32, payload))
+        .collect()
+    }
+
+    proptest! {
+        #![proptest_config(ProptestConfig::with_cases(20))]
+        #[test]
+        fn test_encodings(r in arb_field(), x in arb_field(), y in arb_field()) {
+            for message in messages(r, x, y) {
+                assert_eq!(Message::from_bytes(&message.to_bytes().unwrap()), Ok(message.clone()));
+                assert_eq!(Message::from_cbor(&message.to_cbor()), Ok(message.clone()));
+                assert_eq!(Message::from_json(&message.to_json()), Ok(message))
+            }
+        }
+    }
+
+    proptest! {
+        #![proptest_config(ProptestConfig::with_cases(20))]
+        #[test]
+        fn test_strict_decoding(r in arb_field(), x in arb_field(), y in arb_field()) {
+            for message in messages(r, x, y) {
+                let bytes = message.to_bytes().unwrap();
+                assert_eq!(Message::from_bytes(&bytes[..bytes.len() - 1]), Err(WireError::Truncated));
+                assert_eq!(Message::from_bytes(&[&bytes[..], &[0]].concat()), Err(WireError::TrailingBytes));
+
+                let mut cbor = message.to_cbor();
+                cbor.push(0);
+                assert_eq!(Message::from_cbor(&cbor), Err(WireError::TrailingBytes));
+
+                let mut other = message.clone();
+                other.version = VERSION + 1;
+                assert_eq!(Message::from_bytes(&other.to_bytes().unwrap()), Err(WireError::UnsupportedVersion(VERSION + 1)));
+                assert_eq!(Message::from_json(&other.to_json()), Err(WireError::UnsupportedVersion(VERSION + 1)))
+            }
+        }
+    }
+
+    #[test]
+    fn test_future_version() {
+        // a later version may change the layout of everything but the version
+        #[derive(Serialize)]
+        struct Future {
+            version: u8,
+            payload: Vec<u64>,
+        }
+        let future = Future {
+            version: VERSION + 1,
+            payload: vec![1, 2],
+        };
+        let mut cbor = Vec::new();
+        ciborium::into_writer(&future, &mut cbor).unwrap();
+        let json = serde_json::to_string(&future).unwrap();
+
+        assert_eq!(
+            Message::from_cbor(&cbor),
+            Err(WireError::UnsupportedVersion(VERSION + 1))
+        );
+        assert_eq!(
+            Message::from_json(&json),
+            Err(WireError::UnsupportedVersion(VERSION + 1))
+        );
+        assert!(matches!(
+            Message::from_json("{}"),
+            Err(WireError::Encoding(_))
+        ))
+    }
+
+    #[test]
+    fn test_invalid_values() {
+        let message = Message::new([0; 32], 0, Payload::PartialSignature { s: Scalar::one() });
+        let mut bytes = message.to_bytes().unwrap();
+        bytes[38..].copy_from_slice(&[0xff; 32]);
+        assert_eq!(Message::from_bytes(&bytes), Err(WireError::InvalidScalar));
+
+        // order two point (0, -1)
+        bytes[1] = NONCE_REVEAL;
+        bytes[38..].copy_from_slice(&(-jubjub::Base::one()).to_bytes());
+        assert_eq!(Message::from_bytes(&bytes), Err(WireError::InvalidPoint));
+
+        bytes[1] = 0xff;
+        assert_eq!(
+            Message::from_bytes(&bytes),
+            Err(WireError::UnknownKind(0xff))
+        );
+
+        let empty = Message::new(
+            [0; 32],
+            0,
+            Payload::KeyAgg(KeyAggMessage {
+                keys: vec![],
+                tweaks: vec![],
+            }),
+        );
+        assert_eq!(
+            Message::from_bytes(&empty.to_bytes().unwrap()),
+            Err(WireError::InvalidContext)
+        );
+        assert!(Message::from_json(
+            &empty
+                .to_json()
+                .replace("\"signer\"", "\"extra\":0,\"signer\"")
+        )
+        .is_err());
+    }
+}
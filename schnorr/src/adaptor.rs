@@ -0,0 +1,117 @@
+//! Adaptor signatures encrypted under a point `T = t * G`
+//!
+//! The pre-signature commits to `R + T` in the challenge, so it only becomes a
+//! valid signature once `t` is added, and publishing the signature reveals `t`.
+use crate::hash::{ChallengeHash, TaggedHash};
+use crate::private::PrivateKey;
+use crate::public::PublicKey;
+use crate::signature::{challenge, Signature};
+
+use jubjub::affine::Affine;
+use jubjub::scalar::Scalar;
+use rand_core::RngCore;
+
+/// pre-signature `(s', e)` with `e = H(R + T, m)` and `s' = k - x * e`
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct PreSignature {
+    pub(crate) s: Scalar,
+    pub(crate) e: Scalar,
+}
+
+impl PreSignature {
+    pub fn new(s: Scalar, e: Scalar) -> Self {
+        Self { s, e }
+    }
+
+    pub fn get_s(&self) -> Scalar {
+        self.s
+    }
+
+    pub fn get_e(&self) -> Scalar {
+        self.e
+    }
+
+    /// complete the signature with the adaptor secret, `s = s' + t`
+    pub fn adapt(&self, t: Scalar) -> Signature {
+        Signature::new(self.s + t, self.e)
+    }
+
+    /// recover the adaptor secret from the published signature, `t = s - s'`
+    pub fn extract(&self, sig: &Signature) -> Scalar {
+        sig.get_s() - self.s
+    }
+}
+
+impl PrivateKey {
+    pub fn pre_sign(&self, m: &[u8], adaptor: Affine, rand: impl RngCore) -> PreSignature {
+        self.pre_sign_with::<TaggedHash>(m, adaptor, rand)
+    }
+
+    pub fn pre_sign_with<H: ChallengeHash>(
+        &self,
+        m: &[u8],
+        adaptor: Affine,
+        rand: impl RngCore,
+    ) -> PreSignature {
+        let k = Scalar::random(rand);
+        let r = Affine::basepoint() * k + adaptor;
+        let e = challenge::<H>(&r.to_affine(), m);
+        let s = k - self.0 * e;
+
+        PreSignature::new(s, e)
+    }
+}
+
+impl PublicKey {
+    pub fn verify_pre_signature(self, m: &[u8], adaptor: Affine, pre: &PreSignature) -> bool {
+        self.verify_pre_signature_with::<TaggedHash>(m, adaptor, pre)
+    }
+
+    /// `H(s' * G + e * Y + T, m) == e`
+    pub fn verify_pre_signature_with<H: ChallengeHash>(
+        self,
+        m: &[u8],
+        adaptor: Affine,
+        pre: &PreSignature,
+    ) -> bool {
+        let r_v = Affine::basepoint() * pre.s + self.0 * pre.e + adaptor;
+        let e_v = challenge::<H>(&r_v.to_affine(), m);
+
+        e_v == pre.e
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use proptest::{collection::vec, prelude::*};
+    use rand_core::OsRng;
+
+    prop_compose! {
+        fn arb_field()(
+            bytes in vec(any::<u8>(), 64)
+        ) -> Scalar {
+            Scalar::from_bytes_wide(&<[u8; 64]>::try_from(bytes).unwrap())
+        }
+    }
+
+    proptest! {
+        #![proptest_config(ProptestConfig::with_cases(20))]
+        #[test]
+        fn test_adaptor_signature(x in arb_field(), t in arb_field()) {
+            let message = b"swap";
+            let private_key = PrivateKey::new(x);
+            let public_key = private_key.to_public_key();
+            let adaptor = (Affine::basepoint() * t).to_affine();
+
+            let pre = private_key.pre_sign(message, adaptor, OsRng);
+            assert!(public_key.verify_pre_signature(message, adaptor, &pre));
+            assert!(!public_key.verify_pre_signature(message, Affine::basepoint(), &pre));
+            assert!(!public_key.verify(message, Signature::new(pre.get_s(), pre.get_e())));
+
+            let signature = pre.adapt(t);
+            assert_eq!(pre.extract(&signature), t);
+            assert!(public_key.verify(message, signature))
+        }
+    }
+}
@@ -0,0 +1,130 @@
+//! Blind Schnorr signatures
+//!
+//! 1. issuer: `k` random, sends `R = k * G`
+//! 2. user: `alpha, beta` random, `R' = R + alpha * G + beta * Y`,
+//!    `e' = H(R', m)`, sends `e = e' - beta`
+//! 3. issuer: sends `s = k - x * e`
+//! 4. user: `(s + alpha, e')` verifies under `Y` with the ordinary verifier
+//!
+//! Each issuer session consumes its nonce so `k` can't answer two challenges.
+//! Running many sessions concurrently is still unsafe: with more than a few
+//! hundred open sessions the ROS attack forges an extra signature, so the
+//! issuer has to bound or serialize outstanding sessions.
+use crate::hash::TaggedHash;
+use crate::private::PrivateKey;
+use crate::public::PublicKey;
+use crate::signature::{challenge, Signature};
+
+use jubjub::affine::Affine;
+use jubjub::scalar::Scalar;
+use rand_core::RngCore;
+
+/// issuer side holding the session nonce `k`
+#[derive(Debug)]
+pub struct IssuerSession {
+    private_key: PrivateKey,
+    nonce: Scalar,
+}
+
+impl IssuerSession {
+    /// start a session, returns the commitment `R` to send to the user
+    pub fn commit(private_key: PrivateKey, rand: impl RngCore) -> (Self, Affine) {
+        let nonce = Scalar::random(rand);
+        let commitment = (Affine::basepoint() * nonce).to_affine();
+
+        (Self { private_key, nonce }, commitment)
+    }
+
+    /// `s = k - x * e`, consumes the session
+    pub fn respond(self, challenge: Scalar) -> Scalar {
+        self.nonce - self.private_key * challenge
+    }
+}
+
+/// user side holding the blinding factors
+#[derive(Debug)]
+pub struct UserSession {
+    public_key: PublicKey,
+    alpha: Scalar,
+    // R'
+    randomness: Affine,
+    // e'
+    challenge: Scalar,
+}
+
+impl UserSession {
+    /// blind the issuer commitment, returns the challenge `e` to send to the issuer
+    pub fn blind(
+        public_key: PublicKey,
+        commitment: Affine,
+        m: &[u8],
+        mut rand: impl RngCore,
+    ) -> (Self, Scalar) {
+        let alpha = Scalar::random(&mut rand);
+        let beta = Scalar::random(&mut rand);
+        let randomness = (commitment + Affine::basepoint() * alpha + public_key * beta).to_affine();
+        let e = challenge::<TaggedHash>(&randomness, m);
+
+        let session = Self {
+            public_key,
+            alpha,
+            randomness,
+            challenge: e,
+        };
+        (session, e - beta)
+    }
+
+    /// `None` if the issuer response doesn't match its commitment
+    pub fn unblind(self, response: Scalar) -> Option<Signature> {
+        let s = response + self.alpha;
+        let r_v = Affine::basepoint() * s + self.public_key * self.challenge;
+        if r_v.to_affine() == self.randomness {
+            Some(Signature::new(s, self.challenge))
+        } else {
+            None
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use proptest::{collection::vec, prelude::*};
+    use rand_core::OsRng;
+
+    prop_compose! {
+        fn arb_field()(
+            bytes in vec(any::<u8>(), 64)
+        ) -> Scalar {
+            Scalar::from_bytes_wide(&<[u8; 64]>::try_from(bytes).unwrap())
+        }
+    }
+
+    proptest! {
+        #![proptest_config(ProptestConfig::with_cases(20))]
+        #[test]
+        fn test_blind_signature(x in arb_field()) {
+            let message = b"token";
+            let private_key = PrivateKey::new(x);
+            let public_key = private_key.to_public_key();
+
+            let (issuer, commitment) = IssuerSession::commit(private_key, OsRng);
+            let (user, challenge) = UserSession::blind(public_key, commitment, message, OsRng);
+            let signature = user.unblind(issuer.respond(challenge)).unwrap();
+
+            assert_ne!(signature.get_e(), challenge);
+            assert!(public_key.verify(message, signature))
+        }
+    }
+
+    #[test]
+    fn test_invalid_response() {
+        let private_key = PrivateKey::new(Scalar::random(OsRng));
+        let public_key = private_key.to_public_key();
+
+        let (_, commitment) = IssuerSession::commit(private_key, OsRng);
+        let (user, _) = UserSession::blind(public_key, commitment, b"token", OsRng);
+
+        assert!(user.unblind(Scalar::random(OsRng)).is_none())
+    }
+}
@@ -0,0 +1,89 @@
+//! Diffie-Hellman key agreement
+use crate::private::PrivateKey;
+use crate::public::PublicKey;
+
+use blake2b_simd::Params;
+use jubjub::extend::Extended;
+
+const KDF_PERSONAL: &[u8; 16] = b"RedDSA_ECDH_KDF_";
+
+/// shared point `[8 * x] Y`, never the identity
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct SharedSecret(Extended);
+
+impl SharedSecret {
+    /// `None` if the peer key yields a low order point
+    pub fn new(private_key: &PrivateKey, public_key: &PublicKey) -> Option<Self> {
+        let point = (*public_key * private_key.0).mul_by_cofactor();
+        if point.is_identity() {
+            None
+        } else {
+            Some(Self(point))
+        }
+    }
+
+    /// K = H(len(context) || context || P), distinct contexts give independent keys
+    pub fn derive_key(&self, context: &[u8]) -> [u8; 32] {
+        let digest = Params::new()
+            .hash_length(32)
+            .personal(KDF_PERSONAL)
+            .to_state()
+            .update(&(context.len() as u64).to_le_bytes())
+            .update(context)
+            .update(&self.0.to_bytes())
+            .finalize();
+        digest.as_bytes().try_into().unwrap()
+    }
+}
+
+/// shared key between `private_key` and peer `public_key` bound to `context`
+pub fn diffie_hellman(
+    private_key: &PrivateKey,
+    public_key: &PublicKey,
+    context: &[u8],
+) -> Option<[u8; 32]> {
+    SharedSecret::new(private_key, public_key).map(|secret| secret.derive_key(context))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use jubjub::affine::Affine;
+    use jubjub::scalar::Scalar;
+    use jubjub::Base;
+    use proptest::{collection::vec, prelude::*};
+
+    prop_compose! {
+        fn arb_field()(
+            bytes in vec(any::<u8>(), 64)
+        ) -> Scalar {
+            Scalar::from_bytes_wide(&<[u8; 64]>::try_from(bytes).unwrap())
+        }
+    }
+
+    proptest! {
+        #![proptest_config(ProptestConfig::with_cases(20))]
+        #[test]
+        fn test_key_agreement(a in arb_field(), b in arb_field()) {
+            let alice = PrivateKey::new(a);
+            let bob = PrivateKey::new(b);
+            let alice_key = diffie_hellman(&alice, &bob.to_public_key(), b"nonce");
+            let bob_key = diffie_hellman(&bob, &alice.to_public_key(), b"nonce");
+
+            assert!(alice_key.is_some());
+            assert_eq!(alice_key, bob_key);
+            assert_ne!(alice_key, diffie_hellman(&alice, &bob.to_public_key(), b"other"))
+        }
+    }
+
+    #[test]
+    fn test_low_order_rejected() {
+        let private_key = PrivateKey::new(Scalar::one());
+        // (0, -1)
+        let order_two = Affine::from_bytes((-Base::one()).to_bytes()).unwrap();
+
+        assert!(diffie_hellman(&private_key, &PublicKey::new(order_two), b"").is_none());
+        assert!(PublicKey::from_bytes(order_two.to_bytes()).is_none());
+        assert!(PublicKey::from_bytes(private_key.to_public_key().to_bytes()).is_some());
+    }
+}
@@ -1,41 +1,188 @@
 use blake2b_simd::{Params, State};
+use jubjub::poseidon::{PoseidonParams, Sponge};
 use jubjub::scalar::Scalar;
+use jubjub::Base;
+use sha2::{Digest, Sha512};
 
-const SCHNORR_PERSONAL: &[u8; 16] = b"Schnorr_Sig_Hash";
+const TAGGED_PERSONAL: &[u8; 16] = b"MultiRedDSA_Tags";
+const BLAKE2S_PERSONAL: &[u8; 8] = b"RedDSA_T";
 
-pub struct SchnorrHash(State);
+/// domain separation tags
+pub mod tag {
+    /// key aggregation coefficient `a_i = H_agg(L, X_i)`
+    pub const AGG: &[u8] = b"MuSig/agg";
+    /// nonce commitment `t_i = H_com(R_i)`
+    pub const COM: &[u8] = b"MuSig/com";
+    /// signature challenge `c = H_sig(R, X, m)`
+    pub const SIG: &[u8] = b"Schnorr/sig";
+    /// nonce derivation `r = H_non(x, rand, m)`
+    pub const NON: &[u8] = b"Schnorr/non";
+    /// taproot output key tweak `t = H_tap(P, root)`
+    pub const TAP_TWEAK: &[u8] = b"Taproot/tweak";
+    /// ring signature challenge `c_{i+1} = H_ring(L, I, m, L_i, R_i)`
+    pub const RING: &[u8] = b"LSAG/ring";
+    /// proof of possession `e = H_pop(R, Y)`
+    pub const POP: &[u8] = b"Schnorr/pop";
+    /// VRF proof challenge `c = H_vrf(Y, H, Gamma, U, V)`
+    pub const VRF: &[u8] = b"VRF/challenge";
+    /// VRF output `beta = H_out(8 * Gamma)`
+    pub const VRF_OUT: &[u8] = b"VRF/output";
+}
+
+/// BLAKE2b hash bound to a tag, every input is length framed
+#[derive(Clone)]
+pub struct TaggedHash(State);
 
-impl Default for SchnorrHash {
-    fn default() -> Self {
+impl TaggedHash {
+    pub fn new(tag: &[u8]) -> Self {
         let state = Params::new()
             .hash_length(64)
-            .personal(SCHNORR_PERSONAL)
+            .personal(TAGGED_PERSONAL)
             .to_state();
 
-        Self(state)
+        Self(state).chain(tag)
+    }
+
+    pub fn chain(mut self, bytes: &[u8]) -> Self {
+        self.0.update(&(bytes.len() as u64).to_le_bytes());
+        self.0.update(bytes);
+        self
+    }
+
+    pub fn finalize(&self) -> Scalar {
+        Scalar::from_bytes_wide(&self.finalize_bytes())
+    }
+
+    pub fn finalize_bytes(&self) -> [u8; 64] {
+        *self.0.finalize().as_array()
     }
 }
 
-impl SchnorrHash {
-    fn update(&mut self, bytes: &[u8]) -> &mut Self {
+/// hash backend deriving the signature challenge from tagged, length framed inputs
+pub trait ChallengeHash: Clone {
+    fn new(tag: &[u8]) -> Self;
+
+    fn chain(self, bytes: &[u8]) -> Self;
+
+    fn finalize(&self) -> Scalar;
+}
+
+impl ChallengeHash for TaggedHash {
+    fn new(tag: &[u8]) -> Self {
+        TaggedHash::new(tag)
+    }
+
+    fn chain(self, bytes: &[u8]) -> Self {
+        TaggedHash::chain(self, bytes)
+    }
+
+    fn finalize(&self) -> Scalar {
+        TaggedHash::finalize(self)
+    }
+}
+
+/// BLAKE2s backend, two domain separated digests are joined for the wide reduction
+#[derive(Clone)]
+pub struct Blake2sHash(blake2s_simd::State);
+
+impl ChallengeHash for Blake2sHash {
+    fn new(tag: &[u8]) -> Self {
+        let state = blake2s_simd::Params::new()
+            .hash_length(32)
+            .personal(BLAKE2S_PERSONAL)
+            .to_state();
+
+        Self(state).chain(tag)
+    }
+
+    fn chain(mut self, bytes: &[u8]) -> Self {
+        self.0.update(&(bytes.len() as u64).to_le_bytes());
+        self.0.update(bytes);
+        self
+    }
+
+    fn finalize(&self) -> Scalar {
+        let mut wide = [0; 64];
+        wide[..32].copy_from_slice(self.0.clone().update(&[0]).finalize().as_bytes());
+        wide[32..].copy_from_slice(self.0.clone().update(&[1]).finalize().as_bytes());
+        Scalar::from_bytes_wide(&wide)
+    }
+}
+
+/// SHA-512 backend
+#[derive(Clone)]
+pub struct Sha512Hash(Sha512);
+
+impl ChallengeHash for Sha512Hash {
+    fn new(tag: &[u8]) -> Self {
+        Self(Sha512::new()).chain(tag)
+    }
+
+    fn chain(mut self, bytes: &[u8]) -> Self {
+        self.0.update((bytes.len() as u64).to_le_bytes());
         self.0.update(bytes);
         self
     }
 
     fn finalize(&self) -> Scalar {
-        let digest = self.0.finalize();
-        Scalar::from_bytes_wide(digest.as_array())
+        let digest: [u8; 64] = self.0.clone().finalize().into();
+        Scalar::from_bytes_wide(&digest)
+    }
+}
+
+/// Poseidon backend over the Jubjub base field, bytes are absorbed in 31 byte chunks
+#[derive(Clone)]
+pub struct PoseidonHash(Sponge<'static>);
+
+impl ChallengeHash for PoseidonHash {
+    fn new(tag: &[u8]) -> Self {
+        Self(Sponge::new(PoseidonParams::width3(), Base::zero())).chain(tag)
+    }
+
+    fn chain(mut self, bytes: &[u8]) -> Self {
+        self.0.absorb(Base::from(bytes.len() as u64));
+        for chunk in bytes.chunks(31) {
+            let mut element = [0; 32];
+            element[..chunk.len()].copy_from_slice(chunk);
+            self.0.absorb(Base::from_bytes(element).unwrap());
+        }
+        self
     }
 
-    pub fn execute(a: &[u8], b: &[u8]) -> Scalar {
-        SchnorrHash::default().update(a).update(b).finalize()
+    fn finalize(&self) -> Scalar {
+        let mut wide = [0; 64];
+        wide[..32].copy_from_slice(&self.0.clone().squeeze().to_bytes());
+        Scalar::from_bytes_wide(&wide)
     }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_framing_and_tags() {
+        let joined = TaggedHash::new(tag::SIG)
+            .chain(b"ab")
+            .chain(b"c")
+            .finalize();
+        let split = TaggedHash::new(tag::SIG)
+            .chain(b"a")
+            .chain(b"bc")
+            .finalize();
+        let tagged = TaggedHash::new(tag::AGG)
+            .chain(b"ab")
+            .chain(b"c")
+            .finalize();
 
-    pub fn aggregate(a: &[u8], b: &[u8], c: &[u8]) -> Scalar {
-        SchnorrHash::default()
-            .update(a)
-            .update(b)
-            .update(c)
-            .finalize()
+        assert_ne!(joined, split);
+        assert_ne!(joined, tagged);
+        assert_eq!(
+            joined,
+            TaggedHash::new(tag::SIG)
+                .chain(b"ab")
+                .chain(b"c")
+                .finalize()
+        );
     }
 }
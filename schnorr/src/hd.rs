@@ -0,0 +1,352 @@
+//! Hierarchical deterministic key derivation
+extern crate alloc;
+
+use crate::private::PrivateKey;
+use crate::public::PublicKey;
+
+use alloc::vec::Vec;
+use blake2b_simd::Params;
+use jubjub::affine::Affine;
+use jubjub::scalar::Scalar;
+
+const MASTER_PERSONAL: &[u8; 16] = b"RedDSA_HD_Master";
+const CHILD_PERSONAL: &[u8; 16] = b"RedDSA_HD_Child_";
+const EXPAND_PERSONAL: &[u8; 16] = b"RedDSA_HD_Expand";
+const FINGERPRINT_PERSONAL: &[u8; 16] = b"RedDSA_HD_Finger";
+
+/// first hardened child index
+pub const HARDENED: u32 = 1 << 31;
+
+/// child index with the hardened flag separated from the index
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ChildIndex {
+    Hardened(u32),
+    NonHardened(u32),
+}
+
+impl ChildIndex {
+    pub fn from_index(index: u32) -> Self {
+        if index & HARDENED == 0 {
+            ChildIndex::NonHardened(index)
+        } else {
+            ChildIndex::Hardened(index & !HARDENED)
+        }
+    }
+
+    /// `None` if the index without the hardened flag needs more than 31 bits
+    pub fn index(self) -> Option<u32> {
+        match self {
+            ChildIndex::Hardened(i) if i < HARDENED => Some(i | HARDENED),
+            ChildIndex::NonHardened(i) if i < HARDENED => Some(i),
+            _ => None,
+        }
+    }
+
+    pub fn is_hardened(self) -> bool {
+        matches!(self, ChildIndex::Hardened(_))
+    }
+}
+
+/// sequence of child indices starting from the master key
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct DerivationPath(Vec<ChildIndex>);
+
+impl DerivationPath {
+    pub fn master() -> Self {
+        Self(Vec::new())
+    }
+
+    pub fn child(&self, index: ChildIndex) -> Self {
+        let mut path = self.0.clone();
+        path.push(index);
+        Self(path)
+    }
+
+    /// parse `m/44'/1/2h` style path
+    pub fn parse(path: &str) -> Option<Self> {
+        let mut segments = path.split('/');
+        if segments.next()? != "m" {
+            return None;
+        }
+
+        let mut indices = Vec::new();
+        for segment in segments {
+            let (digits, hardened) = match segment.strip_suffix(['\'', 'h']) {
+                Some(digits) => (digits, true),
+                None => (segment, false),
+            };
+            let index: u32 = digits.parse().ok()?;
+            if index & HARDENED != 0 {
+                return None;
+            }
+            indices.push(if hardened {
+                ChildIndex::Hardened(index)
+            } else {
+                ChildIndex::NonHardened(index)
+            });
+        }
+
+        Some(Self(indices))
+    }
+
+    pub fn indices(&self) -> &[ChildIndex] {
+        &self.0
+    }
+}
+
+/// extended private key holding the chain code for child derivation
+#[derive(Clone, Copy, Debug)]
+pub struct ExtendedPrivateKey {
+    depth: u8,
+    parent_fingerprint: [u8; 4],
+    child_index: ChildIndex,
+    chain_code: [u8; 32],
+    private_key: PrivateKey,
+}
+
+impl ExtendedPrivateKey {
+    pub fn master(seed: &[u8]) -> Self {
+        let i = Params::new()
+            .hash_length(64)
+            .personal(MASTER_PERSONAL)
+            .hash(seed);
+        let (i_l, i_r) = i.as_bytes().split_at(32);
+
+        Self {
+            depth: 0,
+            parent_fingerprint: [0; 4],
+            child_index: ChildIndex::NonHardened(0),
+            chain_code: i_r.try_into().unwrap(),
+            private_key: PrivateKey(expand(i_l)),
+        }
+    }
+
+    /// `None` for an out of range index or beyond depth 255
+    pub fn derive_child(&self, index: ChildIndex) -> Option<Self> {
+        let depth = self.depth.checked_add(1)?;
+        let public_key = self.private_key.to_public_key();
+        let (tweak, chain_code) = match index {
+            ChildIndex::Hardened(_) => {
+                let mut data = [0; 33];
+                data[1..].copy_from_slice(&self.private_key.0.to_bytes());
+                child(&self.chain_code, &data, index)?
+            }
+            ChildIndex::NonHardened(_) => child(&self.chain_code, &public_key.to_bytes(), index)?,
+        };
+
+        Some(Self {
+            depth,
+            parent_fingerprint: fingerprint(&public_key),
+            child_index: index,
+            chain_code,
+            private_key: PrivateKey(self.private_key.0 + tweak),
+        })
+    }
+
+    pub fn derive_path(&self, path: &DerivationPath) -> Option<Self> {
+        path.indices()
+            .iter()
+            .try_fold(*self, |key, index| key.derive_child(*index))
+    }
+
+    pub fn to_extended_public_key(&self) -> ExtendedPublicKey {
+        ExtendedPublicKey {
+            depth: self.depth,
+            parent_fingerprint: self.parent_fingerprint,
+            child_index: self.child_index,
+            chain_code: self.chain_code,
+            public_key: self.private_key.to_public_key(),
+        }
+    }
+
+    pub fn private_key(&self) -> PrivateKey {
+        self.private_key
+    }
+
+    pub fn chain_code(&self) -> [u8; 32] {
+        self.chain_code
+    }
+
+    pub fn depth(&self) -> u8 {
+        self.depth
+    }
+
+    pub fn parent_fingerprint(&self) -> [u8; 4] {
+        self.parent_fingerprint
+    }
+
+    pub fn child_index(&self) -> ChildIndex {
+        self.child_index
+    }
+}
+
+/// extended public key supporting non-hardened child derivation
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ExtendedPublicKey {
+    depth: u8,
+    parent_fingerprint: [u8; 4],
+    child_index: ChildIndex,
+    chain_code: [u8; 32],
+    public_key: PublicKey,
+}
+
+impl ExtendedPublicKey {
+    /// hardened children can't be derived from public key, `None` for those,
+    /// an out of range index or beyond depth 255
+    pub fn derive_child(&self, index: ChildIndex) -> Option<Self> {
+        if index.is_hardened() {
+            return None;
+        }
+        let depth = self.depth.checked_add(1)?;
+        let (tweak, chain_code) = child(&self.chain_code, &self.public_key.to_bytes(), index)?;
+        let tweak_point = PublicKey::new((Affine::basepoint() * tweak).to_affine());
+
+        Some(Self {
+            depth,
+            parent_fingerprint: fingerprint(&self.public_key),
+            child_index: index,
+            chain_code,
+            public_key: self.public_key + tweak_point,
+        })
+    }
+
+    pub fn derive_path(&self, path: &DerivationPath) -> Option<Self> {
+        path.indices()
+            .iter()
+            .try_fold(*self, |key, index| key.derive_child(*index))
+    }
+
+    pub fn public_key(&self) -> PublicKey {
+        self.public_key
+    }
+
+    pub fn chain_code(&self) -> [u8; 32] {
+        self.chain_code
+    }
+
+    pub fn depth(&self) -> u8 {
+        self.depth
+    }
+
+    pub fn parent_fingerprint(&self) -> [u8; 4] {
+        self.parent_fingerprint
+    }
+
+    pub fn child_index(&self) -> ChildIndex {
+        self.child_index
+    }
+
+    pub fn fingerprint(&self) -> [u8; 4] {
+        fingerprint(&self.public_key)
+    }
+}
+
+// I = H(c_par, data || i), returns (ToScalar(I_L), I_R)
+fn child(chain_code: &[u8; 32], data: &[u8], index: ChildIndex) -> Option<(Scalar, [u8; 32])> {
+    let index = index.index()?;
+    let i = Params::new()
+        .hash_length(64)
+        .key(chain_code)
+        .personal(CHILD_PERSONAL)
+        .to_state()
+        .update(data)
+        .update(&index.to_be_bytes())
+        .finalize();
+    let (i_l, i_r) = i.as_bytes().split_at(32);
+
+    Some((expand(i_l), i_r.try_into().unwrap()))
+}
+
+fn expand(key: &[u8]) -> Scalar {
+    let digest = Params::new()
+        .hash_length(64)
+        .personal(EXPAND_PERSONAL)
+        .to_state()
+        .update(key)
+        .update(&[0])
+        .finalize();
+    Scalar::from_bytes_wide(digest.as_array())
+}
+
+fn fingerprint(public_key: &PublicKey) -> [u8; 4] {
+    let digest = Params::new()
+        .hash_length(32)
+        .personal(FINGERPRINT_PERSONAL)
+        .hash(&public_key.to_bytes());
+    digest.as_bytes()[..4].try_into().unwrap()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use proptest::{collection::vec, prelude::*};
+
+    proptest! {
+        #![proptest_config(ProptestConfig::with_cases(20))]
+        #[test]
+        fn test_public_derivation(seed in vec(any::<u8>(), 32), a in 0..HARDENED, b in 0..HARDENED) {
+            let path = DerivationPath::master()
+                .child(ChildIndex::Hardened(a))
+                .child(ChildIndex::NonHardened(a))
+                .child(ChildIndex::NonHardened(b));
+            let master = ExtendedPrivateKey::master(&seed);
+            let account = master.derive_child(ChildIndex::Hardened(a)).unwrap();
+
+            let private = master.derive_path(&path).unwrap().to_extended_public_key();
+            let public = account
+                .to_extended_public_key()
+                .derive_child(ChildIndex::NonHardened(a))
+                .and_then(|key| key.derive_child(ChildIndex::NonHardened(b)))
+                .unwrap();
+
+            assert_eq!(private, public);
+            assert!(account.to_extended_public_key().derive_child(ChildIndex::Hardened(b)).is_none())
+        }
+    }
+
+    #[test]
+    fn test_derivation_path() {
+        let path = DerivationPath::parse("m/44'/133h/0/7").unwrap();
+        let expected = DerivationPath::master()
+            .child(ChildIndex::Hardened(44))
+            .child(ChildIndex::Hardened(133))
+            .child(ChildIndex::NonHardened(0))
+            .child(ChildIndex::NonHardened(7));
+
+        assert_eq!(path, expected);
+        assert_eq!(
+            ChildIndex::from_index(44 | HARDENED),
+            ChildIndex::Hardened(44)
+        );
+        assert!(DerivationPath::parse("44'/0").is_none());
+        assert!(DerivationPath::parse("m/2147483648").is_none());
+    }
+
+    #[test]
+    fn test_invalid_derivation() {
+        let master = ExtendedPrivateKey::master(b"seed");
+
+        assert_eq!(ChildIndex::NonHardened(HARDENED).index(), None);
+        assert!(master
+            .derive_child(ChildIndex::NonHardened(HARDENED))
+            .is_none());
+        assert!(master
+            .derive_child(ChildIndex::Hardened(HARDENED))
+            .is_none());
+        assert!(master
+            .to_extended_public_key()
+            .derive_child(ChildIndex::NonHardened(HARDENED | 1))
+            .is_none());
+
+        // depth is a u8, derivation stops at 255
+        let deepest = (0..255).fold(master, |key, _| {
+            key.derive_child(ChildIndex::NonHardened(0)).unwrap()
+        });
+        assert_eq!(deepest.depth(), 255);
+        assert!(deepest.derive_child(ChildIndex::Hardened(0)).is_none());
+        assert!(deepest
+            .to_extended_public_key()
+            .derive_child(ChildIndex::NonHardened(0))
+            .is_none());
+    }
+}
@@ -1,12 +1,22 @@
 #![no_std]
+pub mod adaptor;
+pub mod blind;
+pub mod ecdh;
 pub mod hash;
+pub mod hd;
+pub mod pop;
 pub mod private;
 pub mod public;
+pub mod ring;
+pub mod sigma;
 pub mod signature;
+pub mod transcript;
+pub mod vrf;
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use hash::{Blake2sHash, ChallengeHash, PoseidonHash, Sha512Hash, TaggedHash};
     use jubjub::scalar::Scalar;
     use private::PrivateKey;
     use proptest::{collection::vec, prelude::*};
@@ -32,4 +42,28 @@ mod tests {
             assert!(public_key.verify(message, signature))
         }
     }
+
+    // `H` signatures verify with `H` and are rejected by `G`
+    fn check_hash<H: ChallengeHash, G: ChallengeHash>(private_key: PrivateKey) {
+        let message = b"test";
+        let public_key = private_key.to_public_key();
+
+        let signature = private_key.sign_with::<H>(message, OsRng);
+        assert!(public_key.verify_with::<H>(message, signature));
+        let signature = private_key.sign_with::<H>(message, OsRng);
+        assert!(!public_key.verify_with::<G>(message, signature));
+    }
+
+    proptest! {
+        #![proptest_config(ProptestConfig::with_cases(20))]
+        #[test]
+        fn test_challenge_hash(value in arb_field()) {
+            let private_key = PrivateKey::new(value);
+
+            check_hash::<TaggedHash, Blake2sHash>(private_key);
+            check_hash::<Blake2sHash, PoseidonHash>(private_key);
+            check_hash::<PoseidonHash, Sha512Hash>(private_key);
+            check_hash::<Sha512Hash, TaggedHash>(private_key)
+        }
+    }
 }
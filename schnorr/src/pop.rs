@@ -0,0 +1,99 @@
+//! Proof of possession against rogue key attacks
+//!
+//! Summing public keys lets a participant choose `Y' = Y_r - Y_honest` and
+//! control the aggregate. A Schnorr signature over the key itself under a
+//! dedicated tag proves the registrant knows its private key.
+use crate::hash::tag;
+use crate::private::PrivateKey;
+use crate::public::PublicKey;
+use crate::signature::Signature;
+use crate::transcript::Transcript;
+
+use core::ops::Add;
+use jubjub::affine::Affine;
+use jubjub::scalar::Scalar;
+use rand_core::RngCore;
+
+impl PrivateKey {
+    /// signature over the own public key under `tag::POP`
+    pub fn prove_possession(&self, rand: impl RngCore) -> Signature {
+        let k = Scalar::random(rand);
+        let r = (Affine::basepoint() * k).to_affine();
+        let e = challenge(&r, &self.to_public_key());
+        let s = k - self.0 * e;
+
+        Signature::new(s, e)
+    }
+}
+
+/// public key whose proof of possession has been checked
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct PopVerifiedKey(PublicKey);
+
+impl PopVerifiedKey {
+    /// `None` if the key is invalid or the proof doesn't verify
+    pub fn new(public_key: PublicKey, pop: &Signature) -> Option<Self> {
+        PublicKey::from_bytes(public_key.to_bytes())?;
+        let (s, e) = (pop.get_s(), pop.get_e());
+        let r_v = (Affine::basepoint() * s + public_key * e).to_affine();
+
+        if challenge(&r_v, &public_key) == e {
+            Some(Self(public_key))
+        } else {
+            None
+        }
+    }
+
+    pub fn public_key(&self) -> PublicKey {
+        self.0
+    }
+}
+
+impl Add for PopVerifiedKey {
+    type Output = PublicKey;
+
+    fn add(self, rhs: Self) -> PublicKey {
+        self.0 + rhs.0
+    }
+}
+
+// e = H_pop(R, Y)
+fn challenge(r: &Affine, public_key: &PublicKey) -> Scalar {
+    let mut transcript = Transcript::new(tag::POP);
+    transcript.append_point(b"R", r);
+    transcript.append_message(b"Y", &public_key.to_bytes());
+    transcript.challenge_scalar(b"e")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use proptest::{collection::vec, prelude::*};
+    use rand_core::OsRng;
+
+    prop_compose! {
+        fn arb_field()(
+            bytes in vec(any::<u8>(), 64)
+        ) -> Scalar {
+            Scalar::from_bytes_wide(&<[u8; 64]>::try_from(bytes).unwrap())
+        }
+    }
+
+    proptest! {
+        #![proptest_config(ProptestConfig::with_cases(20))]
+        #[test]
+        fn test_proof_of_possession(x in arb_field(), y in arb_field()) {
+            let private_key = PrivateKey::new(x);
+            let public_key = private_key.to_public_key();
+            let pop = private_key.prove_possession(OsRng);
+            let rogue = PrivateKey::new(y).to_public_key();
+
+            assert!(PopVerifiedKey::new(public_key, &pop).is_some());
+            assert!(PopVerifiedKey::new(rogue, &pop).is_none());
+
+            // a proof of possession isn't a signature over the key bytes
+            let sig = private_key.sign(&public_key.to_bytes(), OsRng);
+            assert!(PopVerifiedKey::new(public_key, &sig).is_none())
+        }
+    }
+}
@@ -1,8 +1,8 @@
 use core::ops::Mul;
 
-use crate::hash::SchnorrHash;
+use crate::hash::{ChallengeHash, TaggedHash};
 use crate::public::PublicKey;
-use crate::signature::Signature;
+use crate::signature::{challenge, Signature};
 
 use jubjub::affine::Affine;
 use jubjub::scalar::Scalar;
@@ -16,16 +16,28 @@ impl PrivateKey {
         Self(value)
     }
 
+    pub fn to_bytes(&self) -> [u8; 32] {
+        self.0.to_bytes()
+    }
+
+    pub fn from_bytes(bytes: [u8; 32]) -> Option<Self> {
+        Scalar::from_bytes(bytes).map(Self)
+    }
+
     pub fn to_public_key(self) -> PublicKey {
         let value = Affine::basepoint() * self.0;
         PublicKey(value.to_affine())
     }
 
-    pub(crate) fn sign(&self, m: &[u8], rand: impl RngCore) -> Signature {
+    pub fn sign(&self, m: &[u8], rand: impl RngCore) -> Signature {
+        self.sign_with::<TaggedHash>(m, rand)
+    }
+
+    pub fn sign_with<H: ChallengeHash>(&self, m: &[u8], rand: impl RngCore) -> Signature {
         // chose random
         let k = Scalar::random(rand);
         let r = Affine::basepoint() * k;
-        let e = SchnorrHash::execute(&r.to_affine().to_bytes(), m);
+        let e = challenge::<H>(&r.to_affine(), m);
         let s = k - self.0 * e;
 
         Signature::new(s, e)
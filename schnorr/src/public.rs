@@ -1,12 +1,12 @@
-use crate::hash::SchnorrHash;
-use crate::signature::Signature;
+use crate::hash::{ChallengeHash, TaggedHash};
+use crate::signature::{challenge, Signature};
 
 use core::ops::{Add, Mul};
 use jubjub::affine::Affine;
 use jubjub::extend::Extended;
 use jubjub::scalar::Scalar;
 
-#[derive(Clone, Copy, Debug)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub struct PublicKey(pub(crate) Affine);
 
 impl PublicKey {
@@ -14,20 +14,41 @@ impl PublicKey {
         Self(value)
     }
 
-    pub(crate) fn verify(self, m: &[u8], sig: Signature) -> bool {
+    pub fn verify(self, m: &[u8], sig: Signature) -> bool {
+        self.verify_with::<TaggedHash>(m, sig)
+    }
+
+    pub fn verify_with<H: ChallengeHash>(self, m: &[u8], sig: Signature) -> bool {
         let s = Scalar::from_bytes(sig.s).unwrap();
         let e = Scalar::from_bytes(sig.e).unwrap();
         let r_v = Affine::basepoint() * s + self.0 * e;
-        let e_v = SchnorrHash::execute(&r_v.to_affine().to_bytes(), m);
+        let e_v = challenge::<H>(&r_v.to_affine(), m);
 
         e_v == e
     }
 
+    pub fn to_affine(&self) -> Affine {
+        self.0
+    }
+
     pub fn to_bytes(&self) -> [u8; 32] {
         self.0.to_bytes()
     }
+
+    /// decode point, rejecting small order and non prime order points
+    pub fn from_bytes(bytes: [u8; 32]) -> Option<Self> {
+        let point = Affine::from_bytes(bytes)?;
+        let extended = point.extend();
+        if extended.is_small_order() || !extended.is_torsion_free() {
+            None
+        } else {
+            Some(Self(point))
+        }
+    }
 }
 
+/// plain key sum, only safe for keys whose owner proved possession,
+/// see [`crate::pop::PopVerifiedKey`]
 impl Add for PublicKey {
     type Output = Self;
 
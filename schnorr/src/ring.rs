@@ -0,0 +1,185 @@
+//! Linkable spontaneous anonymous group (LSAG) signatures
+//!
+//! The key image `I = x * H_p(Y)` is the same for every signature made with
+//! `x`, so two signatures sharing it come from the same ring member.
+extern crate alloc;
+
+use crate::hash::tag;
+use crate::private::PrivateKey;
+use crate::public::PublicKey;
+use crate::transcript::Transcript;
+
+use alloc::vec;
+use alloc::vec::Vec;
+use jubjub::affine::Affine;
+use jubjub::extend::Extended;
+use jubjub::hash_to_curve::hash_to_curve;
+use jubjub::scalar::Scalar;
+use rand_core::RngCore;
+
+const KEY_IMAGE_DST: &[u8] = b"MultiRedDSA-V01-CS01-with-jubjub_XMD:BLAKE2b_ELL2_RO_KeyImage";
+
+/// `H_p(Y)`, a point with unknown discrete logarithm per public key
+pub fn hash_to_point(public_key: &PublicKey) -> Extended {
+    hash_to_curve(KEY_IMAGE_DST, &public_key.to_bytes())
+}
+
+/// `I = x * H_p(Y)`
+pub fn key_image(private_key: &PrivateKey) -> Affine {
+    (hash_to_point(&private_key.to_public_key()) * private_key.0).to_affine()
+}
+
+/// ring signature `(c_0, s_0, ..., s_{n-1}, I)`
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RingSignature {
+    challenge: Scalar,
+    responses: Vec<Scalar>,
+    key_image: Affine,
+}
+
+impl RingSignature {
+    /// `None` if `ring[index]` isn't the public key of `private_key`
+    pub fn sign(
+        ring: &[PublicKey],
+        index: usize,
+        private_key: &PrivateKey,
+        m: &[u8],
+        mut rand: impl RngCore,
+    ) -> Option<Self> {
+        if ring.get(index) != Some(&private_key.to_public_key()) {
+            return None;
+        }
+        let n = ring.len();
+        let key_image = key_image(private_key);
+        let transcript = transcript(ring, key_image, m);
+        let points: Vec<Extended> = ring.iter().map(hash_to_point).collect();
+
+        let alpha = Scalar::random(&mut rand);
+        let mut challenges = vec![Scalar::zero(); n];
+        let mut responses = vec![Scalar::zero(); n];
+        challenges[(index + 1) % n] = next_challenge(
+            &transcript,
+            Affine::basepoint() * alpha,
+            points[index] * alpha,
+        );
+
+        for offset in 1..n {
+            let i = (index + offset) % n;
+            responses[i] = Scalar::random(&mut rand);
+            challenges[(i + 1) % n] = next_challenge(
+                &transcript,
+                Affine::basepoint() * responses[i] + ring[i] * challenges[i],
+                points[i] * responses[i] + key_image * challenges[i],
+            );
+        }
+        responses[index] = alpha - private_key.0 * challenges[index];
+
+        Some(Self {
+            challenge: challenges[0],
+            responses,
+            key_image,
+        })
+    }
+
+    pub fn verify(&self, ring: &[PublicKey], m: &[u8]) -> bool {
+        let image = self.key_image.extend();
+        if ring.len() != self.responses.len()
+            || ring.is_empty()
+            || image.is_identity()
+            || !image.is_torsion_free()
+        {
+            return false;
+        }
+        let transcript = transcript(ring, self.key_image, m);
+
+        let challenge =
+            ring.iter()
+                .zip(self.responses.iter())
+                .fold(self.challenge, |c, (public_key, s)| {
+                    next_challenge(
+                        &transcript,
+                        Affine::basepoint() * *s + *public_key * c,
+                        hash_to_point(public_key) * *s + self.key_image * c,
+                    )
+                });
+
+        challenge == self.challenge
+    }
+
+    /// both signatures were made by the same private key
+    pub fn is_linked(&self, other: &Self) -> bool {
+        self.key_image == other.key_image
+    }
+
+    pub fn key_image(&self) -> Affine {
+        self.key_image
+    }
+}
+
+fn transcript(ring: &[PublicKey], key_image: Affine, m: &[u8]) -> Transcript {
+    let mut transcript = Transcript::new(tag::RING);
+    transcript.append_u64(b"n", ring.len() as u64);
+    for public_key in ring {
+        transcript.append_message(b"Y", &public_key.to_bytes());
+    }
+    transcript.append_point(b"I", &key_image);
+    transcript.append_message(b"m", m);
+    transcript
+}
+
+fn next_challenge(transcript: &Transcript, l: Extended, r: Extended) -> Scalar {
+    let mut transcript = transcript.clone();
+    transcript.append_point(b"L", &l.to_affine());
+    transcript.append_point(b"R", &r.to_affine());
+    transcript.challenge_scalar(b"c")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use proptest::{collection::vec, prelude::*};
+    use rand_core::OsRng;
+
+    prop_compose! {
+        fn arb_field()(
+            bytes in vec(any::<u8>(), 64)
+        ) -> Scalar {
+            Scalar::from_bytes_wide(&<[u8; 64]>::try_from(bytes).unwrap())
+        }
+    }
+
+    proptest! {
+        #![proptest_config(ProptestConfig::with_cases(10))]
+        #[test]
+        fn test_ring_signature(keys in vec(arb_field(), 1..5), index in any::<prop::sample::Index>()) {
+            let private_keys: Vec<PrivateKey> = keys.into_iter().map(PrivateKey::new).collect();
+            let ring: Vec<PublicKey> = private_keys.iter().map(|key| key.to_public_key()).collect();
+            let index = index.index(ring.len());
+            let signer = &private_keys[index];
+
+            let first = RingSignature::sign(&ring, index, signer, b"yes", OsRng).unwrap();
+            let second = RingSignature::sign(&ring, index, signer, b"no", OsRng).unwrap();
+
+            assert!(first.verify(&ring, b"yes"));
+            assert!(!first.verify(&ring, b"no"));
+            assert!(!first.verify(&ring[1..], b"yes"));
+            assert!(first.is_linked(&second))
+        }
+    }
+
+    #[test]
+    fn test_unlinked_signers() {
+        let private_keys = [
+            PrivateKey::new(Scalar::random(OsRng)),
+            PrivateKey::new(Scalar::random(OsRng)),
+        ];
+        let ring = private_keys.map(|key| key.to_public_key());
+
+        let first = RingSignature::sign(&ring, 0, &private_keys[0], b"vote", OsRng).unwrap();
+        let second = RingSignature::sign(&ring, 1, &private_keys[1], b"vote", OsRng).unwrap();
+
+        assert!(second.verify(&ring, b"vote"));
+        assert!(!first.is_linked(&second));
+        assert!(RingSignature::sign(&ring, 1, &private_keys[0], b"vote", OsRng).is_none())
+    }
+}
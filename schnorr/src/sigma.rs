@@ -0,0 +1,486 @@
+//! Sigma protocols over Jubjub and their Fiat-Shamir transformation
+//!
+//! A relation proves knowledge of its witness with commitments `T`, a
+//! challenge `c` and responses `s`. Simulation from a given challenge is what
+//! makes OR composition possible: the prover simulates the branch it can't
+//! answer and splits the challenge between both branches.
+extern crate alloc;
+
+use crate::transcript::Transcript;
+
+use alloc::vec;
+use alloc::vec::Vec;
+use jubjub::extend::Extended;
+use jubjub::scalar::Scalar;
+use rand_core::RngCore;
+
+/// three move public coin proof of knowledge
+pub trait Sigma {
+    type Witness;
+    /// prover randomness between commitment and response
+    type State;
+
+    /// public points the proof is bound to
+    fn statement(&self) -> Vec<Extended>;
+
+    fn commitment_len(&self) -> usize;
+
+    fn response_len(&self) -> usize;
+
+    fn commit<R: RngCore>(
+        &self,
+        witness: &Self::Witness,
+        rand: &mut R,
+    ) -> (Self::State, Vec<Extended>);
+
+    fn respond(
+        &self,
+        witness: &Self::Witness,
+        state: Self::State,
+        challenge: Scalar,
+    ) -> Vec<Scalar>;
+
+    /// accepting transcript for a given challenge without the witness
+    fn simulate<R: RngCore>(&self, challenge: Scalar, rand: &mut R)
+        -> (Vec<Extended>, Vec<Scalar>);
+
+    fn verify(&self, commitments: &[Extended], challenge: Scalar, responses: &[Scalar]) -> bool;
+}
+
+/// knowledge of `x` such that `Y = x * G`
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct DLog {
+    pub base: Extended,
+    pub point: Extended,
+}
+
+impl DLog {
+    pub fn new(base: Extended, point: Extended) -> Self {
+        Self { base, point }
+    }
+}
+
+impl Sigma for DLog {
+    type Witness = Scalar;
+    type State = Scalar;
+
+    fn statement(&self) -> Vec<Extended> {
+        vec![self.base, self.point]
+    }
+
+    fn commitment_len(&self) -> usize {
+        1
+    }
+
+    fn response_len(&self) -> usize {
+        1
+    }
+
+    fn commit<R: RngCore>(&self, _: &Scalar, rand: &mut R) -> (Scalar, Vec<Extended>) {
+        let k = Scalar::random(rand);
+        (k, vec![self.base * k])
+    }
+
+    // s = k + c * x
+    fn respond(&self, witness: &Scalar, state: Scalar, challenge: Scalar) -> Vec<Scalar> {
+        vec![state + challenge * *witness]
+    }
+
+    fn simulate<R: RngCore>(
+        &self,
+        challenge: Scalar,
+        rand: &mut R,
+    ) -> (Vec<Extended>, Vec<Scalar>) {
+        let s = Scalar::random(rand);
+        (vec![self.base * s - self.point * challenge], vec![s])
+    }
+
+    // s * G == T + c * Y
+    fn verify(&self, commitments: &[Extended], challenge: Scalar, responses: &[Scalar]) -> bool {
+        if !lengths_match(self, commitments, responses) {
+            return false;
+        }
+        self.base * responses[0] == commitments[0] + self.point * challenge
+    }
+}
+
+/// Chaum-Pedersen proof that `Y = x * G` and `Z = x * H` share `x`
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Dleq {
+    pub g: Extended,
+    pub y: Extended,
+    pub h: Extended,
+    pub z: Extended,
+}
+
+impl Dleq {
+    pub fn new(g: Extended, y: Extended, h: Extended, z: Extended) -> Self {
+        Self { g, y, h, z }
+    }
+}
+
+impl Sigma for Dleq {
+    type Witness = Scalar;
+    type State = Scalar;
+
+    fn statement(&self) -> Vec<Extended> {
+        vec![self.g, self.y, self.h, self.z]
+    }
+
+    fn commitment_len(&self) -> usize {
+        2
+    }
+
+    fn response_len(&self) -> usize {
+        1
+    }
+
+    fn commit<R: RngCore>(&self, _: &Scalar, rand: &mut R) -> (Scalar, Vec<Extended>) {
+        let k = Scalar::random(rand);
+        (k, vec![self.g * k, self.h * k])
+    }
+
+    fn respond(&self, witness: &Scalar, state: Scalar, challenge: Scalar) -> Vec<Scalar> {
+        vec![state + challenge * *witness]
+    }
+
+    fn simulate<R: RngCore>(
+        &self,
+        challenge: Scalar,
+        rand: &mut R,
+    ) -> (Vec<Extended>, Vec<Scalar>) {
+        let s = Scalar::random(rand);
+        let commitments = vec![
+            self.g * s - self.y * challenge,
+            self.h * s - self.z * challenge,
+        ];
+        (commitments, vec![s])
+    }
+
+    fn verify(&self, commitments: &[Extended], challenge: Scalar, responses: &[Scalar]) -> bool {
+        if !lengths_match(self, commitments, responses) {
+            return false;
+        }
+        self.g * responses[0] == commitments[0] + self.y * challenge
+            && self.h * responses[0] == commitments[1] + self.z * challenge
+    }
+}
+
+/// both relations hold, under the same challenge
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct And<A, B>(pub A, pub B);
+
+impl<A: Sigma, B: Sigma> Sigma for And<A, B> {
+    type Witness = (A::Witness, B::Witness);
+    type State = (A::State, B::State);
+
+    fn statement(&self) -> Vec<Extended> {
+        let mut statement = self.0.statement();
+        statement.extend(self.1.statement());
+        statement
+    }
+
+    fn commitment_len(&self) -> usize {
+        self.0.commitment_len() + self.1.commitment_len()
+    }
+
+    fn response_len(&self) -> usize {
+        self.0.response_len() + self.1.response_len()
+    }
+
+    fn commit<R: RngCore>(
+        &self,
+        witness: &Self::Witness,
+        rand: &mut R,
+    ) -> (Self::State, Vec<Extended>) {
+        let (state_a, mut commitments) = self.0.commit(&witness.0, rand);
+        let (state_b, commitments_b) = self.1.commit(&witness.1, rand);
+        commitments.extend(commitments_b);
+        ((state_a, state_b), commitments)
+    }
+
+    fn respond(
+        &self,
+        witness: &Self::Witness,
+        state: Self::State,
+        challenge: Scalar,
+    ) -> Vec<Scalar> {
+        let mut responses = self.0.respond(&witness.0, state.0, challenge);
+        responses.extend(self.1.respond(&witness.1, state.1, challenge));
+        responses
+    }
+
+    fn simulate<R: RngCore>(
+        &self,
+        challenge: Scalar,
+        rand: &mut R,
+    ) -> (Vec<Extended>, Vec<Scalar>) {
+        let (mut commitments, mut responses) = self.0.simulate(challenge, rand);
+        let (commitments_b, responses_b) = self.1.simulate(challenge, rand);
+        commitments.extend(commitments_b);
+        responses.extend(responses_b);
+        (commitments, responses)
+    }
+
+    fn verify(&self, commitments: &[Extended], challenge: Scalar, responses: &[Scalar]) -> bool {
+        if !lengths_match(self, commitments, responses) {
+            return false;
+        }
+        let (t_a, t_b) = commitments.split_at(self.0.commitment_len());
+        let (s_a, s_b) = responses.split_at(self.0.response_len());
+        self.0.verify(t_a, challenge, s_a) && self.1.verify(t_b, challenge, s_b)
+    }
+}
+
+/// at least one relation holds, without revealing which
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Or<A, B>(pub A, pub B);
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum OrWitness<A, B> {
+    Left(A),
+    Right(B),
+}
+
+/// real branch state with the simulated challenge and responses of the other branch
+#[derive(Clone, Debug)]
+pub enum OrState<A, B> {
+    Left(A, Scalar, Vec<Scalar>),
+    Right(B, Scalar, Vec<Scalar>),
+}
+
+impl<A: Sigma, B: Sigma> Sigma for Or<A, B> {
+    type Witness = OrWitness<A::Witness, B::Witness>;
+    type State = OrState<A::State, B::State>;
+
+    fn statement(&self) -> Vec<Extended> {
+        let mut statement = self.0.statement();
+        statement.extend(self.1.statement());
+        statement
+    }
+
+    fn commitment_len(&self) -> usize {
+        self.0.commitment_len() + self.1.commitment_len()
+    }
+
+    // c_a || s_a || s_b
+    fn response_len(&self) -> usize {
+        1 + self.0.response_len() + self.1.response_len()
+    }
+
+    fn commit<R: RngCore>(
+        &self,
+        witness: &Self::Witness,
+        rand: &mut R,
+    ) -> (Self::State, Vec<Extended>) {
+        let simulated = Scalar::random(&mut *rand);
+        match witness {
+            OrWitness::Left(w) => {
+                let (state, mut commitments) = self.0.commit(w, rand);
+                let (commitments_b, responses_b) = self.1.simulate(simulated, rand);
+                commitments.extend(commitments_b);
+                (OrState::Left(state, simulated, responses_b), commitments)
+            }
+            OrWitness::Right(w) => {
+                let (state, commitments_b) = self.1.commit(w, rand);
+                let (mut commitments, responses_a) = self.0.simulate(simulated, rand);
+                commitments.extend(commitments_b);
+                (OrState::Right(state, simulated, responses_a), commitments)
+            }
+        }
+    }
+
+    /// panics if the witness branch differs from the committed one
+    fn respond(
+        &self,
+        witness: &Self::Witness,
+        state: Self::State,
+        challenge: Scalar,
+    ) -> Vec<Scalar> {
+        match (witness, state) {
+            (OrWitness::Left(w), OrState::Left(state, c_b, responses_b)) => {
+                let c_a = challenge - c_b;
+                let mut responses = vec![c_a];
+                responses.extend(self.0.respond(w, state, c_a));
+                responses.extend(responses_b);
+                responses
+            }
+            (OrWitness::Right(w), OrState::Right(state, c_a, responses_a)) => {
+                let mut responses = vec![c_a];
+                responses.extend(responses_a);
+                responses.extend(self.1.respond(w, state, challenge - c_a));
+                responses
+            }
+            _ => panic!("witness doesn't match the committed branch"),
+        }
+    }
+
+    fn simulate<R: RngCore>(
+        &self,
+        challenge: Scalar,
+        rand: &mut R,
+    ) -> (Vec<Extended>, Vec<Scalar>) {
+        let c_a = Scalar::random(&mut *rand);
+        let (mut commitments, responses_a) = self.0.simulate(c_a, rand);
+        let (commitments_b, responses_b) = self.1.simulate(challenge - c_a, rand);
+        commitments.extend(commitments_b);
+
+        let mut responses = vec![c_a];
+        responses.extend(responses_a);
+        responses.extend(responses_b);
+        (commitments, responses)
+    }
+
+    fn verify(&self, commitments: &[Extended], challenge: Scalar, responses: &[Scalar]) -> bool {
+        if !lengths_match(self, commitments, responses) {
+            return false;
+        }
+        let (t_a, t_b) = commitments.split_at(self.0.commitment_len());
+        let (s_a, s_b) = responses[1..].split_at(self.0.response_len());
+        let c_a = responses[0];
+        self.0.verify(t_a, c_a, s_a) && self.1.verify(t_b, challenge - c_a, s_b)
+    }
+}
+
+/// non-interactive proof, the challenge is squeezed from the caller's transcript
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Proof {
+    commitments: Vec<Extended>,
+    responses: Vec<Scalar>,
+}
+
+impl Proof {
+    pub fn new<S: Sigma, R: RngCore>(
+        transcript: &mut Transcript,
+        relation: &S,
+        witness: &S::Witness,
+        mut rand: R,
+    ) -> Self {
+        let (state, commitments) = relation.commit(witness, &mut rand);
+        let challenge = challenge(transcript, relation, &commitments);
+        let responses = relation.respond(witness, state, challenge);
+
+        Self {
+            commitments,
+            responses,
+        }
+    }
+
+    pub fn verify<S: Sigma>(&self, transcript: &mut Transcript, relation: &S) -> bool {
+        if !lengths_match(relation, &self.commitments, &self.responses) {
+            return false;
+        }
+        let challenge = challenge(transcript, relation, &self.commitments);
+        relation.verify(&self.commitments, challenge, &self.responses)
+    }
+
+    pub fn commitments(&self) -> &[Extended] {
+        &self.commitments
+    }
+
+    pub fn responses(&self) -> &[Scalar] {
+        &self.responses
+    }
+}
+
+// checked before any `verify` indexes into the slices
+fn lengths_match<S: Sigma>(relation: &S, commitments: &[Extended], responses: &[Scalar]) -> bool {
+    commitments.len() == relation.commitment_len() && responses.len() == relation.response_len()
+}
+
+// c = H(transcript, statement, commitments)
+fn challenge<S: Sigma>(
+    transcript: &mut Transcript,
+    relation: &S,
+    commitments: &[Extended],
+) -> Scalar {
+    transcript.append_u64(b"statement", relation.statement().len() as u64);
+    for point in relation.statement() {
+        transcript.append_point(b"X", &point.to_affine());
+    }
+    for point in commitments {
+        transcript.append_point(b"T", &point.to_affine());
+    }
+    transcript.challenge_scalar(b"c")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use jubjub::affine::Affine;
+    use jubjub::pedersen::nums_generator;
+    use proptest::{collection::vec, prelude::*};
+    use rand_core::OsRng;
+
+    prop_compose! {
+        fn arb_field()(
+            bytes in vec(any::<u8>(), 64)
+        ) -> Scalar {
+            Scalar::from_bytes_wide(&<[u8; 64]>::try_from(bytes).unwrap())
+        }
+    }
+
+    // h has no known discrete log relative to g
+    fn generators() -> (Extended, Extended) {
+        (
+            Affine::basepoint().extend(),
+            nums_generator(b"sigma-test", 0),
+        )
+    }
+
+    proptest! {
+        #![proptest_config(ProptestConfig::with_cases(20))]
+        #[test]
+        fn test_dlog_and_dleq(x in arb_field(), y in arb_field()) {
+            let (g, h) = generators();
+            let dlog = DLog::new(g, g * x);
+            let dleq = Dleq::new(g, g * x, h, h * x);
+
+            let proof = Proof::new(&mut Transcript::new(b"dlog"), &dlog, &x, OsRng);
+            assert!(proof.verify(&mut Transcript::new(b"dlog"), &dlog));
+            assert!(!proof.verify(&mut Transcript::new(b"other"), &dlog));
+            assert!(!Proof::new(&mut Transcript::new(b"dlog"), &dlog, &y, OsRng).verify(&mut Transcript::new(b"dlog"), &dlog));
+
+            let proof = Proof::new(&mut Transcript::new(b"dleq"), &dleq, &x, OsRng);
+            assert!(proof.verify(&mut Transcript::new(b"dleq"), &dleq));
+            assert!(!proof.verify(&mut Transcript::new(b"dleq"), &Dleq::new(g, g * x, h, h * y)))
+        }
+    }
+
+    proptest! {
+        #![proptest_config(ProptestConfig::with_cases(10))]
+        #[test]
+        fn test_composition(x in arb_field(), y in arb_field()) {
+            let (g, h) = generators();
+            let known = DLog::new(g, g * x);
+            let unknown = DLog::new(h, h * y);
+
+            let and = And(known, unknown);
+            let proof = Proof::new(&mut Transcript::new(b"and"), &and, &(x, y), OsRng);
+            assert!(proof.verify(&mut Transcript::new(b"and"), &and));
+
+            let or = Or(known, unknown);
+            let left = Proof::new(&mut Transcript::new(b"or"), &or, &OrWitness::Left(x), OsRng);
+            let right = Proof::new(&mut Transcript::new(b"or"), &or, &OrWitness::Right(y), OsRng);
+            assert!(left.verify(&mut Transcript::new(b"or"), &or));
+            assert!(right.verify(&mut Transcript::new(b"or"), &or));
+            assert!(!Proof::new(&mut Transcript::new(b"or"), &or, &OrWitness::Left(y), OsRng).verify(&mut Transcript::new(b"or"), &or))
+        }
+    }
+
+    #[test]
+    fn test_malformed_lengths() {
+        let (g, h) = generators();
+        let dlog = DLog::new(g, g);
+        let dleq = Dleq::new(g, g, h, h);
+        let nested = And(Or(dlog, dleq), dleq);
+        let c = Scalar::one();
+
+        assert!(!dlog.verify(&[], c, &[]));
+        assert!(!dleq.verify(&[g], c, &[c]));
+        assert!(!And(dlog, dleq).verify(&[g, g], c, &[c, c]));
+        assert!(!Or(dlog, dleq).verify(&[g, g, g], c, &[]));
+        assert!(!nested.verify(&[g; 6], c, &[c; 3]));
+        assert!(!nested.verify(&[g; 2], c, &[c; 5]));
+    }
+}
@@ -1,3 +1,7 @@
+use crate::hash::{tag, ChallengeHash};
+use crate::transcript::Transcript;
+
+use jubjub::affine::Affine;
 use jubjub::scalar::Scalar;
 
 pub struct Signature {
@@ -20,4 +24,27 @@ impl Signature {
     pub fn get_e(&self) -> Scalar {
         Scalar::from_bytes(self.e).unwrap()
     }
+
+    /// `s || e`
+    pub fn to_bytes(&self) -> [u8; 64] {
+        let mut bytes = [0; 64];
+        bytes[..32].copy_from_slice(&self.s);
+        bytes[32..].copy_from_slice(&self.e);
+        bytes
+    }
+
+    /// `None` unless both scalars are canonical
+    pub fn from_bytes(bytes: &[u8; 64]) -> Option<Self> {
+        let s = Scalar::from_bytes(bytes[..32].try_into().unwrap())?;
+        let e = Scalar::from_bytes(bytes[32..].try_into().unwrap())?;
+        Some(Self::new(s, e))
+    }
+}
+
+// e = H_sig(R, m)
+pub(crate) fn challenge<H: ChallengeHash>(r: &Affine, m: &[u8]) -> Scalar {
+    let mut transcript = Transcript::<H>::with_hash(tag::SIG);
+    transcript.append_point(b"R", r);
+    transcript.append_message(b"m", m);
+    transcript.challenge_scalar(b"e")
 }
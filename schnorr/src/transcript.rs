@@ -0,0 +1,75 @@
+//! Fiat-Shamir transcript
+//!
+//! Every protocol message is appended under a label and challenges are
+//! squeezed from everything appended so far, then fed back into the
+//! transcript so later challenges depend on earlier ones.
+use crate::hash::{ChallengeHash, TaggedHash};
+
+use jubjub::affine::Affine;
+use jubjub::scalar::Scalar;
+
+/// labeled transcript over a challenge hash, the protocol name is the tag
+#[derive(Clone)]
+pub struct Transcript<H: ChallengeHash = TaggedHash>(H);
+
+impl Transcript {
+    pub fn new(protocol: &[u8]) -> Self {
+        Self::with_hash(protocol)
+    }
+}
+
+impl<H: ChallengeHash> Transcript<H> {
+    pub fn with_hash(protocol: &[u8]) -> Self {
+        Self(H::new(protocol))
+    }
+
+    pub fn append_message(&mut self, label: &[u8], message: &[u8]) {
+        self.0 = self.0.clone().chain(label).chain(message);
+    }
+
+    pub fn append_point(&mut self, label: &[u8], point: &Affine) {
+        self.append_message(label, &point.to_bytes());
+    }
+
+    pub fn append_scalar(&mut self, label: &[u8], scalar: &Scalar) {
+        self.append_message(label, &scalar.to_bytes());
+    }
+
+    pub fn append_u64(&mut self, label: &[u8], value: u64) {
+        self.append_message(label, &value.to_le_bytes());
+    }
+
+    /// challenge bound to the transcript so far, appended back under the same label
+    pub fn challenge_scalar(&mut self, label: &[u8]) -> Scalar {
+        let challenge = self.0.clone().chain(label).finalize();
+        self.append_scalar(label, &challenge);
+        challenge
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::hash::Sha512Hash;
+
+    #[test]
+    fn test_transcript() {
+        let mut a = Transcript::new(b"test");
+        let mut b = Transcript::new(b"test");
+        a.append_message(b"m", b"ab");
+        a.append_message(b"m", b"c");
+        b.append_message(b"m", b"a");
+        b.append_message(b"m", b"bc");
+        assert_ne!(a.challenge_scalar(b"c"), b.challenge_scalar(b"c"));
+
+        let mut a = Transcript::new(b"test");
+        let mut b = Transcript::new(b"other");
+        assert_ne!(a.challenge_scalar(b"c"), b.challenge_scalar(b"c"));
+
+        let mut a = Transcript::<Sha512Hash>::with_hash(b"test");
+        let mut b = a.clone();
+        let first = a.challenge_scalar(b"c");
+        assert_ne!(first, a.challenge_scalar(b"c"));
+        assert_eq!(first, b.challenge_scalar(b"c"))
+    }
+}
@@ -0,0 +1,178 @@
+//! ECVRF over Jubjub
+//!
+//! Follows the RFC 9381 construction with encode_to_curve, a deterministic
+//! nonce and a 128 bit challenge, the proof is `Gamma || c || s`.
+extern crate alloc;
+
+use crate::hash::{tag, TaggedHash};
+use crate::private::PrivateKey;
+use crate::public::PublicKey;
+use crate::transcript::Transcript;
+
+use alloc::vec::Vec;
+use jubjub::affine::Affine;
+use jubjub::extend::Extended;
+use jubjub::hash_to_curve::encode_to_curve;
+use jubjub::scalar::Scalar;
+
+const VRF_DST: &[u8] = b"MultiRedDSA-V01-CS01-with-jubjub_XMD:BLAKE2b_ELL2_NU_VRF";
+
+// challenge length in bytes
+const C_LEN: usize = 16;
+
+/// proof `(Gamma, c, s)` that `Gamma = x * H(Y, alpha)`
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct VrfProof {
+    gamma: Affine,
+    c: Scalar,
+    s: Scalar,
+}
+
+impl VrfProof {
+    pub fn prove(private_key: &PrivateKey, alpha: &[u8]) -> Self {
+        let public_key = private_key.to_public_key();
+        let h = hash_to_point(&public_key, alpha);
+        let gamma = (h * private_key.0).to_affine();
+
+        // k = H_non(x, H)
+        let k = TaggedHash::new(tag::NON)
+            .chain(&private_key.0.to_bytes())
+            .chain(&h.to_bytes())
+            .finalize();
+        let c = challenge(&public_key, h, gamma, Affine::basepoint() * k, h * k);
+        let s = k + c * private_key.0;
+
+        Self { gamma, c, s }
+    }
+
+    /// the VRF output `beta` if the proof is valid for `public_key` and `alpha`
+    pub fn verify(&self, public_key: &PublicKey, alpha: &[u8]) -> Option<[u8; 64]> {
+        PublicKey::from_bytes(public_key.to_bytes())?;
+        let h = hash_to_point(public_key, alpha);
+
+        // U = s * G - c * Y, V = s * H - c * Gamma
+        let u = Affine::basepoint() * self.s - *public_key * self.c;
+        let v = h * self.s - self.gamma * self.c;
+
+        if challenge(public_key, h, self.gamma, u, v) == self.c {
+            Some(self.proof_to_hash())
+        } else {
+            None
+        }
+    }
+
+    /// `beta = H_out(8 * Gamma)`
+    pub fn proof_to_hash(&self) -> [u8; 64] {
+        let point = self.gamma.extend().mul_by_cofactor();
+        TaggedHash::new(tag::VRF_OUT)
+            .chain(&point.to_bytes())
+            .finalize_bytes()
+    }
+
+    pub fn to_bytes(&self) -> [u8; 80] {
+        let mut bytes = [0; 80];
+        bytes[..32].copy_from_slice(&self.gamma.to_bytes());
+        bytes[32..48].copy_from_slice(&self.c.to_bytes()[..C_LEN]);
+        bytes[48..].copy_from_slice(&self.s.to_bytes());
+        bytes
+    }
+
+    pub fn from_bytes(bytes: &[u8; 80]) -> Option<Self> {
+        let gamma = Affine::from_bytes(bytes[..32].try_into().unwrap())?;
+        let mut c = [0; 32];
+        c[..C_LEN].copy_from_slice(&bytes[32..48]);
+
+        Some(Self {
+            gamma,
+            c: Scalar::from_bytes(c)?,
+            s: Scalar::from_bytes(bytes[48..].try_into().unwrap())?,
+        })
+    }
+}
+
+fn hash_to_point(public_key: &PublicKey, alpha: &[u8]) -> Extended {
+    let mut msg = Vec::with_capacity(32 + alpha.len());
+    msg.extend_from_slice(&public_key.to_bytes());
+    msg.extend_from_slice(alpha);
+    encode_to_curve(VRF_DST, &msg)
+}
+
+// c = H_vrf(Y, H, Gamma, U, V) truncated to 128 bits
+fn challenge(
+    public_key: &PublicKey,
+    h: Extended,
+    gamma: Affine,
+    u: Extended,
+    v: Extended,
+) -> Scalar {
+    let mut transcript = Transcript::new(tag::VRF);
+    transcript.append_message(b"Y", &public_key.to_bytes());
+    transcript.append_point(b"H", &h.to_affine());
+    transcript.append_point(b"Gamma", &gamma);
+    transcript.append_point(b"U", &u.to_affine());
+    transcript.append_point(b"V", &v.to_affine());
+    let digest = transcript.challenge_scalar(b"c").to_bytes();
+    let mut c = [0; 32];
+    c[..C_LEN].copy_from_slice(&digest[..C_LEN]);
+    Scalar::from_bytes(c).unwrap()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::string::String;
+    use proptest::{collection::vec, prelude::*};
+
+    prop_compose! {
+        fn arb_field()(
+            bytes in vec(any::<u8>(), 64)
+        ) -> Scalar {
+            Scalar::from_bytes_wide(&<[u8; 64]>::try_from(bytes).unwrap())
+        }
+    }
+
+    fn to_hex(bytes: &[u8]) -> String {
+        use core::fmt::Write;
+        let mut hex = String::new();
+        bytes.iter().for_each(|b| write!(hex, "{:02x}", b).unwrap());
+        hex
+    }
+
+    // outputs of this implementation pinned against regressions, there is
+    // no published ECVRF suite for Jubjub to check interoperability with
+    #[test]
+    fn test_regression_vectors() {
+        let private_key = PrivateKey::new(Scalar::from(42));
+        let public_key = private_key.to_public_key();
+        let proof = VrfProof::prove(&private_key, b"sample");
+
+        assert_eq!(
+            to_hex(&proof.to_bytes()),
+            "232a2d545a29c53b470f3ec5989c8602689539bb98d7d102b676cb6a547c0f2a\
+             d0952f3f290e4b467d50b070fa2dea85\
+             32b4e513e77f851b081c4f138b8fe877aa295e35bbfbcd178844ea2eb22b8f03"
+        );
+        assert_eq!(
+            to_hex(&proof.verify(&public_key, b"sample").unwrap()),
+            "022040ca6e678236c59daa2c1831f274f09e1aaf109250f891080d67cea39c51\
+             84d4e33bef8f79a295c1eb86c48ab4c36d69a329d7bdfdb99347d58f25ee9d5c"
+        );
+    }
+
+    proptest! {
+        #![proptest_config(ProptestConfig::with_cases(20))]
+        #[test]
+        fn test_prove_verify(x in arb_field(), alpha in vec(any::<u8>(), 0..64)) {
+            let private_key = PrivateKey::new(x);
+            let public_key = private_key.to_public_key();
+            let proof = VrfProof::prove(&private_key, &alpha);
+            let beta = proof.verify(&public_key, &alpha);
+
+            assert_eq!(beta, Some(proof.proof_to_hash()));
+            assert_eq!(VrfProof::from_bytes(&proof.to_bytes()), Some(proof));
+            assert_eq!(VrfProof::prove(&private_key, &alpha), proof);
+            assert!(proof.verify(&public_key, b"other").is_none());
+            assert!(proof.verify(&PrivateKey::new(x + Scalar::one()).to_public_key(), &alpha).is_none())
+        }
+    }
+}
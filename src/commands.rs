@@ -0,0 +1,151 @@
+//! Single signer and key aggregation commands
+use crate::input::{read_array, Result};
+
+use jubjub::scalar::Scalar;
+use musig::key_agg::KeyAggContext;
+use musig::signer::{serve, LocalSigner};
+use rand_core::OsRng;
+use schnorr::private::PrivateKey;
+use schnorr::public::PublicKey;
+use schnorr::signature::Signature;
+use std::io;
+#[cfg(unix)]
+use std::os::unix::net::UnixListener;
+use std::path::Path;
+#[cfg(unix)]
+use std::sync::{Arc, Mutex};
+
+pub fn keygen() -> [u8; 32] {
+    PrivateKey::new(Scalar::random(OsRng)).to_bytes()
+}
+
+pub fn private_key(value: &str) -> Result<PrivateKey> {
+    PrivateKey::from_bytes(read_array(value, "private key")?)
+        .ok_or_else(|| "private key is not a canonical scalar".into())
+}
+
+pub fn public_key(value: &str) -> Result<PublicKey> {
+    PublicKey::from_bytes(read_array(value, "public key")?)
+        .ok_or_else(|| "public key is not a prime order point".into())
+}
+
+pub fn pubkey(key: &str) -> Result<[u8; 32]> {
+    Ok(private_key(key)?.to_public_key().to_bytes())
+}
+
+pub fn sign(key: &str, message: &[u8]) -> Result<[u8; 64]> {
+    Ok(private_key(key)?.sign(message, OsRng).to_bytes())
+}
+
+pub fn verify(pubkey: &str, message: &[u8], signature: &str) -> Result<bool> {
+    let public_key = public_key(pubkey)?;
+    let signature = Signature::from_bytes(&read_array(signature, "signature")?)
+        .ok_or("signature scalars are not canonical")?;
+    Ok(public_key.verify(message, signature))
+}
+
+/// MuSig aggregated key `sum(a_i * X_i)` in the given order
+pub fn aggregate_keys(pubkeys: &[String]) -> Result<[u8; 32]> {
+    let keys = pubkeys
+        .iter()
+        .map(|key| public_key(key))
+        .collect::<Result<Vec<PublicKey>>>()?;
+    let context = KeyAggContext::new(&keys).ok_or("aggregated key is the identity")?;
+    Ok(context.aggregated_key().to_bytes())
+}
+
+/// serve signing requests over stdin and stdout, or each connection to a
+/// Unix socket on its own thread, a failed connection is logged and dropped
+pub fn serve_signer(key: &str, socket: Option<&Path>) -> Result<()> {
+    let mut signer = LocalSigner::new(private_key(key)?);
+    match socket {
+        #[cfg(unix)]
+        Some(path) => {
+            let signer = Arc::new(Mutex::new(signer));
+            for stream in UnixListener::bind(path)?.incoming() {
+                let stream = match stream {
+                    Ok(stream) => stream,
+                    Err(error) => {
+                        eprintln!("accepting a connection failed: {}", error);
+                        continue;
+                    }
+                };
+                let mut signer = signer.clone();
+                std::thread::spawn(move || {
+                    let result = stream
+                        .try_clone()
+                        .and_then(|reader| serve(&mut signer, reader, stream));
+                    if let Err(error) = result {
+                        eprintln!("connection failed: {}", error);
+                    }
+                });
+            }
+        }
+        #[cfg(not(unix))]
+        Some(_) => return Err("Unix sockets are not supported on this platform".into()),
+        None => serve(&mut signer, io::stdin().lock(), io::stdout().lock())?,
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use musig::signer::{RemoteSigner, Signer};
+
+    #[test]
+    fn test_sign_and_verify() {
+        let key = hex::encode(keygen());
+        let pubkey = hex::encode(pubkey(&key).unwrap());
+        let signature = hex::encode(sign(&key, b"test").unwrap());
+
+        assert!(verify(&pubkey, b"test", &signature).unwrap());
+        assert!(!verify(&pubkey, b"other", &signature).unwrap());
+        assert!(verify(&pubkey, b"test", "00").is_err());
+    }
+
+    #[test]
+    fn test_aggregate_keys() {
+        let keys: Vec<String> = (0..3)
+            .map(|_| hex::encode(pubkey(&hex::encode(keygen())).unwrap()))
+            .collect();
+        let reversed: Vec<String> = keys.iter().rev().cloned().collect();
+
+        assert_ne!(
+            aggregate_keys(&keys).unwrap(),
+            aggregate_keys(&reversed).unwrap()
+        );
+        assert!(aggregate_keys(&["00".to_string()]).is_err());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_serve_signer() {
+        let key = hex::encode(keygen());
+        let mut path = std::env::temp_dir();
+        path.push(format!("reddsa-{}-signer.sock", std::process::id()));
+        let socket = path.clone();
+        let served = key.clone();
+        std::thread::spawn(move || serve_signer(&served, Some(&socket)).is_ok());
+
+        let mut signer = loop {
+            match RemoteSigner::connect(&path) {
+                Ok(signer) => break signer,
+                Err(_) => std::thread::sleep(std::time::Duration::from_millis(10)),
+            }
+        };
+        let public_key = signer.public_key().unwrap();
+        assert_eq!(public_key.to_bytes(), pubkey(&key).unwrap());
+        assert!(public_key.verify(b"test", signer.sign(b"test").unwrap()));
+
+        // an idle client doesn't block others and a broken one doesn't stop the server
+        let _idle = std::os::unix::net::UnixStream::connect(&path).unwrap();
+        let mut broken = std::os::unix::net::UnixStream::connect(&path).unwrap();
+        std::io::Write::write_all(&mut broken, &u32::MAX.to_le_bytes()).unwrap();
+        drop(broken);
+        let mut other = RemoteSigner::connect(&path).unwrap();
+        assert_eq!(other.public_key().unwrap(), public_key);
+        assert!(public_key.verify(b"test", signer.sign(b"test").unwrap()));
+        std::fs::remove_file(&path).unwrap();
+    }
+}
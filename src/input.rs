@@ -0,0 +1,81 @@
+//! Hex and file inputs of the command line
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::Path;
+
+pub type Result<T> = std::result::Result<T, Box<dyn std::error::Error>>;
+
+/// hex string, or `@path` to read the hex string from a file
+pub fn read_hex(value: &str) -> Result<Vec<u8>> {
+    let hex = match value.strip_prefix('@') {
+        Some(path) => fs::read_to_string(path)?,
+        None => value.to_string(),
+    };
+    Ok(hex::decode(hex.trim())?)
+}
+
+pub fn read_array<const N: usize>(value: &str, name: &str) -> Result<[u8; N]> {
+    let bytes = read_hex(value)?;
+    bytes
+        .try_into()
+        .map_err(|_| format!("{} must be {} bytes", name, N).into())
+}
+
+/// hex message or raw bytes of a message file
+pub fn read_message(message: Option<&str>, message_file: Option<&Path>) -> Result<Vec<u8>> {
+    match (message, message_file) {
+        (Some(hex), None) => Ok(hex::decode(hex)?),
+        (None, Some(path)) => Ok(fs::read(path)?),
+        _ => Err("exactly one of --message and --message-file is required".into()),
+    }
+}
+
+/// write hex output to a file or stdout
+pub fn write_hex(bytes: &[u8], out: Option<&Path>) -> Result<()> {
+    let hex = hex::encode(bytes);
+    match out {
+        Some(path) => fs::write(path, hex + "\n")?,
+        None => println!("{}", hex),
+    }
+    Ok(())
+}
+
+/// write secret hex output to a file only its owner can read, or stdout
+pub fn write_secret_hex(bytes: &[u8], out: Option<&Path>) -> Result<()> {
+    let hex = hex::encode(bytes);
+    match out {
+        Some(path) => {
+            let mut options = OpenOptions::new();
+            options.write(true).create(true).truncate(true);
+            #[cfg(unix)]
+            std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600);
+            let mut file = options.open(path)?;
+            // the mode only applies on creation, restrict an existing file too
+            #[cfg(unix)]
+            file.set_permissions(std::os::unix::fs::PermissionsExt::from_mode(0o600))?;
+            writeln!(file, "{}", hex)?;
+        }
+        None => println!("{}", hex),
+    }
+    Ok(())
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+    use std::os::unix::fs::PermissionsExt;
+
+    #[test]
+    fn test_write_secret_hex() {
+        let mut path = std::env::temp_dir();
+        path.push(format!("reddsa-{}-secret.hex", std::process::id()));
+        fs::write(&path, "").unwrap();
+        fs::set_permissions(&path, fs::Permissions::from_mode(0o644)).unwrap();
+
+        write_secret_hex(&[1, 2], Some(&path)).unwrap();
+        let mode = fs::metadata(&path).unwrap().permissions().mode();
+        assert_eq!(mode & 0o777, 0o600);
+        assert_eq!(read_hex(&format!("@{}", path.display())).unwrap(), [1, 2]);
+        fs::remove_file(&path).unwrap();
+    }
+}
@@ -1,3 +1,244 @@
-fn main() {
-    println!("Hello, world!");
+mod commands;
+mod input;
+mod rounds;
+mod state;
+
+use input::{read_message, write_hex, write_secret_hex, Result};
+
+use clap::{Args, Parser, Subcommand};
+use std::path::PathBuf;
+use std::process::ExitCode;
+
+/// RedDSA Schnorr and MuSig signatures over Jubjub,
+/// hex arguments can be read from a file with `@path`
+#[derive(Parser)]
+#[command(name = "reddsa", version)]
+struct Cli {
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// generate a random private key
+    Keygen {
+        /// written with owner-only permissions
+        #[arg(long)]
+        out: Option<PathBuf>,
+    },
+    /// derive the public key of a private key
+    Pubkey {
+        /// private key, prefer `@path` since a hex value shows in the process list
+        #[arg(long)]
+        key: String,
+        #[arg(long)]
+        out: Option<PathBuf>,
+    },
+    /// sign a message, the signature is `s || e`
+    Sign {
+        /// private key, prefer `@path` since a hex value shows in the process list
+        #[arg(long)]
+        key: String,
+        #[command(flatten)]
+        message: Message,
+        #[arg(long)]
+        out: Option<PathBuf>,
+    },
+    /// verify a signature, exits with 1 if it's invalid
+    Verify {
+        #[arg(long)]
+        pubkey: String,
+        #[command(flatten)]
+        message: Message,
+        #[arg(long)]
+        signature: String,
+    },
+    /// aggregate public keys with MuSig coefficients
+    AggregateKeys {
+        #[arg(required = true)]
+        pubkeys: Vec<String>,
+        #[arg(long)]
+        out: Option<PathBuf>,
+    },
+    /// offline MuSig signing rounds
+    #[command(subcommand)]
+    Musig(MusigCommand),
+    /// hold a key for a remote signer over stdin/stdout or a Unix socket
+    ServeSigner {
+        /// private key, prefer `@path` since a hex value shows in the process list
+        #[arg(long)]
+        key: String,
+        #[arg(long)]
+        socket: Option<PathBuf>,
+    },
+}
+
+#[derive(Subcommand)]
+enum MusigCommand {
+    /// write the nonce commitment and the encrypted nonce state
+    Commit {
+        /// private key, prefer `@path` since a hex value shows in the process list
+        #[arg(long)]
+        key: String,
+        #[arg(long, num_args = 1.., required = true)]
+        pubkeys: Vec<String>,
+        #[command(flatten)]
+        message: Message,
+        #[arg(long)]
+        state: PathBuf,
+        #[arg(long)]
+        out: Option<PathBuf>,
+    },
+    /// write the nonce after every commitment is collected
+    Reveal {
+        /// private key, prefer `@path` since a hex value shows in the process list
+        #[arg(long)]
+        key: String,
+        #[arg(long)]
+        state: PathBuf,
+        #[arg(long, num_args = 1.., required = true)]
+        pubkeys: Vec<String>,
+        #[arg(long, num_args = 1.., required = true)]
+        commitments: Vec<String>,
+        #[arg(long)]
+        out: Option<PathBuf>,
+    },
+    /// write the partial signature, consumes the nonce state
+    PartialSign {
+        /// private key, prefer `@path` since a hex value shows in the process list
+        #[arg(long)]
+        key: String,
+        #[arg(long)]
+        state: PathBuf,
+        #[arg(long, num_args = 1.., required = true)]
+        pubkeys: Vec<String>,
+        #[command(flatten)]
+        message: Message,
+        #[arg(long, num_args = 1.., required = true)]
+        commitments: Vec<String>,
+        #[arg(long, num_args = 1.., required = true)]
+        nonces: Vec<String>,
+        #[arg(long)]
+        out: Option<PathBuf>,
+    },
+    /// write the verified signature `R || s`
+    Combine {
+        #[arg(long, num_args = 1.., required = true)]
+        pubkeys: Vec<String>,
+        #[command(flatten)]
+        message: Message,
+        #[arg(long, num_args = 1.., required = true)]
+        nonces: Vec<String>,
+        #[arg(long, num_args = 1.., required = true)]
+        partials: Vec<String>,
+        #[arg(long)]
+        out: Option<PathBuf>,
+    },
+}
+
+#[derive(Args)]
+#[group(required = true, multiple = false)]
+struct Message {
+    /// hex encoded message
+    #[arg(long)]
+    message: Option<String>,
+    /// file holding the raw message
+    #[arg(long)]
+    message_file: Option<PathBuf>,
+}
+
+impl Message {
+    fn read(&self) -> Result<Vec<u8>> {
+        read_message(self.message.as_deref(), self.message_file.as_deref())
+    }
+}
+
+fn run(command: Command) -> Result<bool> {
+    match command {
+        Command::Keygen { out } => write_secret_hex(&commands::keygen(), out.as_deref())?,
+        Command::Pubkey { key, out } => write_hex(&commands::pubkey(&key)?, out.as_deref())?,
+        Command::Sign { key, message, out } => {
+            write_hex(&commands::sign(&key, &message.read()?)?, out.as_deref())?
+        }
+        Command::Verify {
+            pubkey,
+            message,
+            signature,
+        } => {
+            let valid = commands::verify(&pubkey, &message.read()?, &signature)?;
+            println!("{}", if valid { "valid" } else { "invalid" });
+            return Ok(valid);
+        }
+        Command::AggregateKeys { pubkeys, out } => {
+            write_hex(&commands::aggregate_keys(&pubkeys)?, out.as_deref())?
+        }
+        Command::Musig(command) => run_musig(command)?,
+        Command::ServeSigner { key, socket } => commands::serve_signer(&key, socket.as_deref())?,
+    }
+    Ok(true)
+}
+
+fn run_musig(command: MusigCommand) -> Result<()> {
+    match command {
+        MusigCommand::Commit {
+            key,
+            pubkeys,
+            message,
+            state,
+            out,
+        } => {
+            let commitment = rounds::commit(&key, &pubkeys, &message.read()?, &state)?;
+            write_hex(&commitment, out.as_deref())
+        }
+        MusigCommand::Reveal {
+            key,
+            state,
+            pubkeys,
+            commitments,
+            out,
+        } => write_hex(
+            &rounds::reveal(&key, &state, &pubkeys, &commitments)?,
+            out.as_deref(),
+        ),
+        MusigCommand::PartialSign {
+            key,
+            state,
+            pubkeys,
+            message,
+            commitments,
+            nonces,
+            out,
+        } => {
+            let partial = rounds::partial_sign(
+                &key,
+                &state,
+                &pubkeys,
+                &message.read()?,
+                &commitments,
+                &nonces,
+            )?;
+            write_hex(&partial, out.as_deref())
+        }
+        MusigCommand::Combine {
+            pubkeys,
+            message,
+            nonces,
+            partials,
+            out,
+        } => {
+            let signature = rounds::combine(&pubkeys, &message.read()?, &nonces, &partials)?;
+            write_hex(&signature, out.as_deref())
+        }
+    }
+}
+
+fn main() -> ExitCode {
+    match run(Cli::parse().command) {
+        Ok(true) => ExitCode::SUCCESS,
+        Ok(false) => ExitCode::FAILURE,
+        Err(err) => {
+            eprintln!("error: {}", err);
+            ExitCode::from(2)
+        }
+    }
 }
@@ -0,0 +1,339 @@
+//! Offline MuSig rounds exchanging hex files
+//!
+//! Every signer runs `commit`, then `reveal` once all commitments are
+//! collected, then `partial-sign` once all nonces are collected. Keys,
+//! commitments, nonces and partial signatures are listed in the same order.
+//! `reveal` binds the state to the commitment list, so the other nonces can't
+//! be changed once `R_i` is out.
+use crate::commands::{private_key, public_key};
+use crate::input::{read_array, Result};
+use crate::state::NonceState;
+
+use blake2b_simd::Params;
+use jubjub::affine::Affine;
+use jubjub::scalar::Scalar;
+use musig::key_agg::KeyAggContext;
+use musig::session::{commit_nonce, Session};
+use rand_core::OsRng;
+use schnorr::public::PublicKey;
+use std::path::Path;
+
+const SESSION_PERSONAL: &[u8; 16] = b"RedDSA_CLI_Sess_";
+const COMMITMENTS_PERSONAL: &[u8; 16] = b"RedDSA_CLI_Comms";
+
+/// first round, stores the secret nonce and returns `H_com(R_i)`
+pub fn commit(key: &str, pubkeys: &[String], message: &[u8], state: &Path) -> Result<[u8; 32]> {
+    let private_key = private_key(key)?;
+    let keys = public_keys(pubkeys)?;
+    if !keys.contains(&private_key.to_public_key()) {
+        return Err("own public key is not one of the signers".into());
+    }
+
+    let r = Scalar::random(OsRng);
+    let nonce = (Affine::basepoint() * r).to_affine();
+    NonceState {
+        r,
+        session_id: session_id(&keys, message),
+        commitments: None,
+    }
+    .save(state, &private_key)?;
+
+    Ok(commit_nonce(&nonce))
+}
+
+/// second round, checks the own commitment and binds the state to the
+/// commitment list before revealing `R_i`
+pub fn reveal(
+    key: &str,
+    state: &Path,
+    pubkeys: &[String],
+    commitments: &[String],
+) -> Result<[u8; 32]> {
+    let private_key = private_key(key)?;
+    let keys = public_keys(pubkeys)?;
+    let commitments = parse_commitments(commitments, keys.len())?;
+    let index = own_index(&keys, &private_key.to_public_key())?;
+
+    let mut nonce_state = NonceState::load(state, &private_key)?;
+    let nonce = (Affine::basepoint() * nonce_state.r).to_affine();
+    if commitments[index] != commit_nonce(&nonce) {
+        return Err("own commitment is not at the own index".into());
+    }
+    let hash = commitments_hash(&commitments);
+    match nonce_state.commitments {
+        Some(stored) if stored != hash => {
+            return Err("nonce was already revealed for other commitments".into())
+        }
+        Some(_) => {}
+        None => {
+            nonce_state.commitments = Some(hash);
+            nonce_state.replace(state, &private_key)?;
+        }
+    }
+    Ok(nonce.to_bytes())
+}
+
+/// third round, checks every nonce against its commitment and consumes the state
+pub fn partial_sign(
+    key: &str,
+    state: &Path,
+    pubkeys: &[String],
+    message: &[u8],
+    commitments: &[String],
+    nonces: &[String],
+) -> Result<[u8; 32]> {
+    let private_key = private_key(key)?;
+    let keys = public_keys(pubkeys)?;
+    let nonces = parse_nonces(nonces, keys.len())?;
+    let commitments = parse_commitments(commitments, keys.len())?;
+    for (i, (commitment, nonce)) in commitments.iter().zip(&nonces).enumerate() {
+        if *commitment != commit_nonce(nonce) {
+            return Err(format!("nonce of signer {} doesn't match its commitment", i).into());
+        }
+    }
+
+    let index = own_index(&keys, &private_key.to_public_key())?;
+    let state = NonceState::consume(state, &private_key)?;
+    if state.session_id != session_id(&keys, message) {
+        return Err("state belongs to a different signer set or message".into());
+    }
+    match state.commitments {
+        None => return Err("nonce was not revealed".into()),
+        Some(hash) if hash != commitments_hash(&commitments) => {
+            return Err("commitments differ from the revealed ones".into())
+        }
+        Some(_) => {}
+    }
+    if (Affine::basepoint() * state.r).to_affine() != nonces[index] {
+        return Err("own nonce differs from the revealed one".into());
+    }
+
+    let context = KeyAggContext::new(&keys).ok_or("aggregated key is the identity")?;
+    let session = Session::new(&context, message, &nonces);
+    let s = session
+        .cosign(state.r, private_key)
+        .ok_or("own public key is not one of the signers")?;
+    Ok(s.to_bytes())
+}
+
+/// sums the partial signatures and verifies the result, `R || s`
+pub fn combine(
+    pubkeys: &[String],
+    message: &[u8],
+    nonces: &[String],
+    partials: &[String],
+) -> Result<[u8; 64]> {
+    let keys = public_keys(pubkeys)?;
+    let nonces = parse_nonces(nonces, keys.len())?;
+    if partials.len() != keys.len() {
+        return Err("one partial signature per signer is required".into());
+    }
+    let partials = partials
+        .iter()
+        .map(|s| {
+            Scalar::from_bytes(read_array(s, "partial signature")?)
+                .ok_or_else(|| "partial signature is not canonical".into())
+        })
+        .collect::<Result<Vec<Scalar>>>()?;
+
+    let context = KeyAggContext::new(&keys).ok_or("aggregated key is the identity")?;
+    let session = Session::new(&context, message, &nonces);
+    let signature = session.generate_signature(&partials);
+    let bytes = signature.to_bytes();
+    if !session.verify(message, signature) {
+        return Err("combined signature is invalid".into());
+    }
+    Ok(bytes)
+}
+
+fn public_keys(pubkeys: &[String]) -> Result<Vec<PublicKey>> {
+    pubkeys.iter().map(|key| public_key(key)).collect()
+}
+
+fn parse_nonces(nonces: &[String], n: usize) -> Result<Vec<Affine>> {
+    if nonces.len() != n {
+        return Err("one nonce per signer is required".into());
+    }
+    nonces
+        .iter()
+        .map(|nonce| public_key(nonce).map(|point| point.to_affine()))
+        .collect()
+}
+
+fn parse_commitments(commitments: &[String], n: usize) -> Result<Vec<[u8; 32]>> {
+    if commitments.len() != n {
+        return Err("one commitment per signer is required".into());
+    }
+    commitments
+        .iter()
+        .map(|commitment| read_array(commitment, "commitment"))
+        .collect()
+}
+
+fn own_index(keys: &[PublicKey], own: &PublicKey) -> Result<usize> {
+    keys.iter()
+        .position(|key| key == own)
+        .ok_or_else(|| "own public key is not one of the signers".into())
+}
+
+// binds a revealed nonce to the commitments of every signer
+fn commitments_hash(commitments: &[[u8; 32]]) -> [u8; 32] {
+    let mut state = Params::new()
+        .hash_length(32)
+        .personal(COMMITMENTS_PERSONAL)
+        .to_state();
+    state.update(&(commitments.len() as u64).to_le_bytes());
+    for commitment in commitments {
+        state.update(commitment);
+    }
+    state.finalize().as_bytes().try_into().unwrap()
+}
+
+// binds a nonce to the signer set and message
+fn session_id(keys: &[PublicKey], message: &[u8]) -> [u8; 32] {
+    let mut state = Params::new()
+        .hash_length(32)
+        .personal(SESSION_PERSONAL)
+        .to_state();
+    state.update(&(keys.len() as u64).to_le_bytes());
+    for key in keys {
+        state.update(&key.to_bytes());
+    }
+    state.update(message);
+    state.finalize().as_bytes().try_into().unwrap()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::commands::{keygen, pubkey};
+    use std::path::PathBuf;
+
+    fn state_path(name: &str) -> PathBuf {
+        let mut path = std::env::temp_dir();
+        path.push(format!(
+            "reddsa-{}-{}-{}",
+            std::process::id(),
+            name,
+            hex::encode(keygen())
+        ));
+        path
+    }
+
+    #[test]
+    fn test_offline_rounds() {
+        let message = b"test";
+        let keys: Vec<String> = (0..3).map(|_| hex::encode(keygen())).collect();
+        let pubkeys: Vec<String> = keys
+            .iter()
+            .map(|key| hex::encode(pubkey(key).unwrap()))
+            .collect();
+        let states: Vec<PathBuf> = (0..3).map(|i| state_path(&i.to_string())).collect();
+
+        let commitments: Vec<String> = keys
+            .iter()
+            .zip(&states)
+            .map(|(key, state)| hex::encode(commit(key, &pubkeys, message, state).unwrap()))
+            .collect();
+        let nonces: Vec<String> = keys
+            .iter()
+            .zip(&states)
+            .map(|(key, state)| hex::encode(reveal(key, state, &pubkeys, &commitments).unwrap()))
+            .collect();
+
+        // a nonce swapped after the commitment round is rejected without consuming the state
+        let mut swapped = nonces.clone();
+        swapped.swap(0, 1);
+        assert!(partial_sign(
+            &keys[0],
+            &states[0],
+            &pubkeys,
+            message,
+            &commitments,
+            &swapped
+        )
+        .is_err());
+
+        let partials: Vec<String> = keys
+            .iter()
+            .zip(&states)
+            .map(|(key, state)| {
+                hex::encode(
+                    partial_sign(key, state, &pubkeys, message, &commitments, &nonces).unwrap(),
+                )
+            })
+            .collect();
+        let signature = combine(&pubkeys, message, &nonces, &partials).unwrap();
+
+        assert!(musig::signature::Signature::from_bytes(&signature).is_some());
+        assert!(states.iter().all(|state| !state.exists()));
+        assert!(partial_sign(
+            &keys[0],
+            &states[0],
+            &pubkeys,
+            message,
+            &commitments,
+            &nonces
+        )
+        .is_err());
+        assert!(combine(&pubkeys, b"other", &nonces, &partials).is_err());
+    }
+
+    #[test]
+    fn test_state_encryption() {
+        let key = hex::encode(keygen());
+        let other = hex::encode(keygen());
+        let pubkeys = vec![hex::encode(pubkey(&key).unwrap())];
+        let state = state_path("encryption");
+
+        let commitments = vec![hex::encode(
+            commit(&key, &pubkeys, b"test", &state).unwrap(),
+        )];
+        assert!(reveal(&other, &state, &pubkeys, &commitments).is_err());
+        assert!(commit(&key, &pubkeys, b"test", &state).is_err());
+        assert!(reveal(&key, &state, &pubkeys, &commitments).is_ok());
+        std::fs::remove_file(&state).unwrap();
+    }
+
+    #[test]
+    fn test_commitments_bound_at_reveal() {
+        let message = b"test";
+        let keys: Vec<String> = (0..2).map(|_| hex::encode(keygen())).collect();
+        let pubkeys: Vec<String> = keys
+            .iter()
+            .map(|key| hex::encode(pubkey(key).unwrap()))
+            .collect();
+        let state = state_path("bound");
+        let other_state = state_path("bound-other");
+
+        let commitments = vec![
+            hex::encode(commit(&keys[0], &pubkeys, message, &state).unwrap()),
+            hex::encode(commit(&keys[1], &pubkeys, message, &other_state).unwrap()),
+        ];
+        let mut reordered = commitments.clone();
+        reordered.swap(0, 1);
+        assert!(reveal(&keys[0], &state, &pubkeys, &commitments[..1]).is_err());
+        assert!(reveal(&keys[0], &state, &pubkeys, &reordered).is_err());
+        let nonce = hex::encode(reveal(&keys[0], &state, &pubkeys, &commitments).unwrap());
+
+        // signer 1 swaps its commitment and nonce after seeing R_0
+        let swapped_state = state_path("bound-swapped");
+        let swapped = hex::encode(commit(&keys[1], &pubkeys, message, &swapped_state).unwrap());
+        let swapped_commitments = vec![commitments[0].clone(), swapped];
+        let swapped_nonce =
+            hex::encode(reveal(&keys[1], &swapped_state, &pubkeys, &swapped_commitments).unwrap());
+        assert!(reveal(&keys[0], &state, &pubkeys, &swapped_commitments).is_err());
+        assert!(partial_sign(
+            &keys[0],
+            &state,
+            &pubkeys,
+            message,
+            &swapped_commitments,
+            &[nonce, swapped_nonce]
+        )
+        .is_err());
+        assert!(!state.exists());
+        std::fs::remove_file(&other_state).unwrap();
+        std::fs::remove_file(&swapped_state).unwrap();
+    }
+}
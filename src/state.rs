@@ -0,0 +1,94 @@
+//! Encrypted secret nonce state consumed by exactly one partial signature
+//!
+//! The file holds `nonce || ChaCha20-Poly1305(r || session id [|| commitments
+//! hash])` under a key derived from the signer's private key. The commitments
+//! hash is added by the reveal round, and the file is removed before the
+//! partial signature is written so a nonce can't be used twice.
+use crate::input::Result;
+
+use blake2b_simd::Params;
+use chacha20poly1305::aead::{Aead, KeyInit};
+use chacha20poly1305::{ChaCha20Poly1305, Key, Nonce};
+use jubjub::scalar::Scalar;
+use rand_core::{OsRng, RngCore};
+use schnorr::private::PrivateKey;
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::Path;
+
+const STATE_PERSONAL: &[u8; 16] = b"RedDSA_CLI_State";
+
+/// secret nonce `r` bound to the session it was generated for
+pub struct NonceState {
+    pub r: Scalar,
+    pub session_id: [u8; 32],
+    /// hash of the commitment list `R_i` was revealed against
+    pub commitments: Option<[u8; 32]>,
+}
+
+impl NonceState {
+    /// fails if the file already exists
+    pub fn save(&self, path: &Path, key: &PrivateKey) -> Result<()> {
+        let mut file = OpenOptions::new().write(true).create_new(true).open(path)?;
+        file.write_all(self.encrypt(key)?.as_bytes())?;
+        Ok(())
+    }
+
+    /// overwrite an existing state file
+    pub fn replace(&self, path: &Path, key: &PrivateKey) -> Result<()> {
+        let mut file = OpenOptions::new().write(true).truncate(true).open(path)?;
+        file.write_all(self.encrypt(key)?.as_bytes())?;
+        Ok(())
+    }
+
+    /// decrypt without consuming the state
+    pub fn load(path: &Path, key: &PrivateKey) -> Result<Self> {
+        let bytes = hex::decode(fs::read_to_string(path)?.trim())?;
+        if bytes.len() < 12 {
+            return Err("state file is truncated".into());
+        }
+        let (nonce, ciphertext) = bytes.split_at(12);
+        let plaintext = cipher(key)
+            .decrypt(Nonce::from_slice(nonce), ciphertext)
+            .map_err(|_| "state file doesn't belong to this key or is corrupted")?;
+        if plaintext.len() != 64 && plaintext.len() != 96 {
+            return Err("state file is malformed".into());
+        }
+
+        Ok(Self {
+            r: Scalar::from_bytes(plaintext[..32].try_into().unwrap())
+                .ok_or("state nonce is not canonical")?,
+            session_id: plaintext[32..64].try_into().unwrap(),
+            commitments: (plaintext.len() == 96).then(|| plaintext[64..].try_into().unwrap()),
+        })
+    }
+
+    /// decrypt and remove the file
+    pub fn consume(path: &Path, key: &PrivateKey) -> Result<Self> {
+        let state = Self::load(path, key)?;
+        fs::remove_file(path)?;
+        Ok(state)
+    }
+
+    fn encrypt(&self, key: &PrivateKey) -> Result<String> {
+        let mut plaintext = self.r.to_bytes().to_vec();
+        plaintext.extend_from_slice(&self.session_id);
+        if let Some(commitments) = self.commitments {
+            plaintext.extend_from_slice(&commitments);
+        }
+        let mut nonce = [0; 12];
+        OsRng.fill_bytes(&mut nonce);
+        let ciphertext = cipher(key)
+            .encrypt(Nonce::from_slice(&nonce), plaintext.as_slice())
+            .map_err(|_| "state encryption failed")?;
+        Ok(hex::encode([&nonce[..], &ciphertext].concat()))
+    }
+}
+
+fn cipher(key: &PrivateKey) -> ChaCha20Poly1305 {
+    let digest = Params::new()
+        .hash_length(32)
+        .personal(STATE_PERSONAL)
+        .hash(&key.to_bytes());
+    ChaCha20Poly1305::new(Key::from_slice(digest.as_bytes()))
+}